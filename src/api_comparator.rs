@@ -0,0 +1,608 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use quote::ToTokens;
+use semver::Version;
+use syn::punctuated::Punctuated;
+use syn::{
+    Attribute, Expr, Fields, ImplItem, Item, Lit, Meta, Token, TraitItem, UseTree, Visibility,
+};
+
+use crate::crate_manifest::CrateManifest;
+use crate::diff_report::DiffReport;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// Public item of the crate API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiItem {
+    /// Item kind: fn, struct, enum, trait, impl, use etc.
+    pub kind: &'static str,
+    /// Full item path starting with the crate name
+    pub path: String,
+    /// Normalized item signature without attributes, private fields and bodies
+    pub signature: String,
+}
+
+/// Public API changes between 2 versions of the crate
+#[derive(Debug, Clone)]
+pub struct ApiDiff {
+    /// Crate name
+    pub name: String,
+    /// Initial crate version
+    pub from_version: Version,
+    /// Target crate version
+    pub to_version: Version,
+    pub added: Vec<ApiItem>,
+    pub removed: Vec<ApiItem>,
+    /// Items with the changed signature: (from, to)
+    pub changed: Vec<(ApiItem, ApiItem)>,
+    /// True if public items were removed in the minor or patch version update
+    pub semver_violation: bool,
+}
+
+/// Compares public API of 2 versions of the updated crates
+pub struct ApiComparator<'a> {
+    registry_manager: &'a RegistryManager,
+}
+
+impl<'a> ApiComparator<'a> {
+    pub fn new(registry_manager: &'a RegistryManager) -> Self {
+        Self { registry_manager }
+    }
+
+    /// Returns public API changes of all updated crates in the report
+    pub fn compare(&self, report: &DiffReport) -> Vec<ApiDiff> {
+        let mut api_diffs = vec![];
+
+        for diff in report.crate_diffs() {
+            let (Some(from_version), Some(to_version)) = (&diff.from_version, &diff.to_version)
+            else {
+                continue;
+            };
+            let Some(from_api) = self.get_crate_api(
+                &diff.name,
+                from_version,
+                diff.from_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+            let Some(mut to_api) = self.get_crate_api(
+                &diff.name,
+                to_version,
+                diff.to_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+
+            let mut removed = vec![];
+            let mut changed = vec![];
+            for (key, from_item) in from_api {
+                match to_api.remove(&key) {
+                    Some(to_item) => {
+                        if from_item.signature != to_item.signature {
+                            changed.push((from_item, to_item));
+                        }
+                    }
+                    None => removed.push(from_item),
+                }
+            }
+            let added: Vec<_> = to_api.into_values().collect();
+
+            if added.is_empty() && removed.is_empty() && changed.is_empty() {
+                continue;
+            }
+
+            let semver_violation = !removed.is_empty()
+                && from_version < to_version
+                && !is_breaking_update(from_version, to_version);
+
+            api_diffs.push(ApiDiff {
+                name: diff.name.clone(),
+                from_version: from_version.clone(),
+                to_version: to_version.clone(),
+                added,
+                removed,
+                changed,
+                semver_violation,
+            });
+        }
+
+        api_diffs
+    }
+
+    /// Collects public items of the crate library target
+    fn get_crate_api(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        hash: Option<&str>,
+        registry: &RegistrySource,
+    ) -> Option<BTreeMap<String, ApiItem>> {
+        let crate_path = self
+            .registry_manager
+            .fetch_crate(crate_name, version, hash, registry)?;
+        let manifest = match CrateManifest::read(&crate_path) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot get API of the '{crate_name}@{version}' crate. Error: {err:#}"
+                );
+                return None;
+            }
+        };
+
+        let lib = manifest.lib.unwrap_or_default();
+        let lib_path = crate_path.join(lib.path.as_deref().unwrap_or("src/lib.rs"));
+        if !lib_path.exists() {
+            // binary crate or unusual layout
+            eprintln!("[WARN] Library target is not found for: {crate_name}@{version}");
+            return None;
+        }
+
+        let crate_root = lib.name.unwrap_or_else(|| crate_name.replace('-', "_"));
+        let mut collector = ApiCollector::default();
+        let mod_dir = lib_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        collector.collect_file(&lib_path, &crate_root, &mod_dir);
+
+        Some(collector.items)
+    }
+}
+
+/// Checks if the version update is allowed to break the API according to the semver rules
+fn is_breaking_update(from_version: &Version, to_version: &Version) -> bool {
+    if from_version.major != to_version.major {
+        return true;
+    }
+    if from_version.major == 0 {
+        if from_version.minor != to_version.minor {
+            return true;
+        }
+        if from_version.minor == 0 {
+            return from_version.patch != to_version.patch;
+        }
+    }
+    false
+}
+
+/// Collects public items of the module tree
+#[derive(Default)]
+struct ApiCollector {
+    /// Public items by '<kind> <path>' key
+    items: BTreeMap<String, ApiItem>,
+}
+
+impl ApiCollector {
+    /// Parses the module file. Nested module files are searched in the `mod_dir` folder.
+    fn collect_file(&mut self, file_path: &Path, module_path: &str, mod_dir: &Path) {
+        let file = match std::fs::read_to_string(file_path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| syn::parse_file(&content).map_err(|err| err.to_string()))
+        {
+            Ok(file) => file,
+            Err(err) => {
+                eprintln!("[WARN] Cannot parse source file {file_path:?}. Error: {err}");
+                return;
+            }
+        };
+
+        let file_dir = file_path.parent().unwrap_or(mod_dir);
+        self.collect_items(&file.items, module_path, file_dir, mod_dir);
+    }
+
+    fn collect_items(
+        &mut self,
+        items: &[Item],
+        module_path: &str,
+        file_dir: &Path,
+        mod_dir: &Path,
+    ) {
+        for item in items {
+            match item {
+                Item::Macro(item) => {
+                    if let Some(ident) = &item.ident
+                        && has_attr(&item.attrs, "macro_export")
+                        && !is_doc_hidden(&item.attrs)
+                    {
+                        // exported macros are always in the crate root
+                        let crate_root = module_path.split("::").next().unwrap_or(module_path);
+                        self.add("macro", format!("{crate_root}::{ident}"), String::new());
+                    }
+                }
+                Item::Impl(item) => self.collect_impl(item, module_path),
+                _ => {}
+            }
+
+            if !is_public(item) {
+                continue;
+            }
+
+            match item {
+                Item::Const(item) => self.add(
+                    "const",
+                    format!("{module_path}::{}", item.ident),
+                    item.ty.to_token_stream().to_string(),
+                ),
+                Item::Enum(item) => {
+                    let mut item = item.clone();
+                    item.attrs.clear();
+                    for variant in &mut item.variants {
+                        variant.attrs.clear();
+                        variant.discriminant = None;
+                        strip_fields(&mut variant.fields, false);
+                    }
+                    self.add(
+                        "enum",
+                        format!("{module_path}::{}", item.ident),
+                        item.to_token_stream().to_string(),
+                    );
+                }
+                Item::Fn(item) => self.add(
+                    "fn",
+                    format!("{module_path}::{}", item.sig.ident),
+                    item.sig.to_token_stream().to_string(),
+                ),
+                Item::Mod(item) => {
+                    let child_path = format!("{module_path}::{}", item.ident);
+                    self.add("mod", child_path.clone(), String::new());
+
+                    let child_mod_dir = mod_dir.join(item.ident.to_string());
+                    if let Some((_, items)) = &item.content {
+                        self.collect_items(items, &child_path, file_dir, &child_mod_dir);
+                    } else if let Some(path) = path_attr(&item.attrs) {
+                        let file_path = file_dir.join(path);
+                        let child_mod_dir = file_path.parent().map(Path::to_path_buf);
+                        self.collect_file(
+                            &file_path,
+                            &child_path,
+                            &child_mod_dir.unwrap_or_default(),
+                        );
+                    } else if let Some(file_path) = Self::find_module_file(mod_dir, &item.ident) {
+                        self.collect_file(&file_path, &child_path, &child_mod_dir);
+                    } else {
+                        eprintln!("[WARN] Cannot find source file of the module: {child_path}");
+                    }
+                }
+                Item::Static(item) => self.add(
+                    "static",
+                    format!("{module_path}::{}", item.ident),
+                    item.ty.to_token_stream().to_string(),
+                ),
+                Item::Struct(item) => {
+                    let mut item = item.clone();
+                    item.attrs.clear();
+                    strip_fields(&mut item.fields, true);
+                    self.add(
+                        "struct",
+                        format!("{module_path}::{}", item.ident),
+                        item.to_token_stream().to_string(),
+                    );
+                }
+                Item::Trait(item) => {
+                    let mut item = item.clone();
+                    item.attrs.clear();
+                    for trait_item in &mut item.items {
+                        match trait_item {
+                            TraitItem::Const(item) => {
+                                item.attrs.clear();
+                                item.default = None;
+                            }
+                            TraitItem::Fn(item) => {
+                                item.attrs.clear();
+                                item.default = None;
+                                item.semi_token = Some(Default::default());
+                            }
+                            TraitItem::Type(item) => {
+                                item.attrs.clear();
+                                item.default = None;
+                            }
+                            _ => {}
+                        }
+                    }
+                    self.add(
+                        "trait",
+                        format!("{module_path}::{}", item.ident),
+                        item.to_token_stream().to_string(),
+                    );
+                }
+                Item::Type(item) => {
+                    let mut item = item.clone();
+                    item.attrs.clear();
+                    self.add(
+                        "type",
+                        format!("{module_path}::{}", item.ident),
+                        item.to_token_stream().to_string(),
+                    );
+                }
+                Item::Union(item) => {
+                    let mut item = item.clone();
+                    item.attrs.clear();
+                    let mut fields = Fields::Named(item.fields.clone());
+                    strip_fields(&mut fields, true);
+                    if let Fields::Named(named) = fields {
+                        item.fields = named;
+                    }
+                    self.add(
+                        "union",
+                        format!("{module_path}::{}", item.ident),
+                        item.to_token_stream().to_string(),
+                    );
+                }
+                Item::Use(item) => {
+                    let mut reexports = vec![];
+                    collect_use_tree(&item.tree, String::new(), &mut reexports);
+                    for (name, source) in reexports {
+                        self.add("use", format!("{module_path}::{name}"), source);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_impl(&mut self, item: &syn::ItemImpl, module_path: &str) {
+        if is_doc_hidden(&item.attrs) {
+            return;
+        }
+
+        let self_ty = item.self_ty.to_token_stream().to_string();
+
+        if let Some((negative, trait_path, _)) = &item.trait_ {
+            // trait implementations are public if both trait and type are public which we cannot check here.
+            // Types with the same name may be implemented in different modules
+            let negative = if negative.is_some() { "!" } else { "" };
+            let trait_path = trait_path.to_token_stream().to_string();
+            self.add(
+                "impl",
+                format!("{module_path}::<{self_ty} as {negative}{trait_path}>"),
+                format!(
+                    "{} {}",
+                    item.generics.to_token_stream(),
+                    item.generics.where_clause.to_token_stream()
+                ),
+            );
+            return;
+        }
+
+        // inherent implementation: only public methods, constants and types
+        let type_name = match item.self_ty.as_ref() {
+            syn::Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .map(|s| s.ident.to_string())
+                .unwrap_or(self_ty),
+            _ => self_ty,
+        };
+        for impl_item in &item.items {
+            match impl_item {
+                ImplItem::Const(item)
+                    if matches!(item.vis, Visibility::Public(_)) && !is_doc_hidden(&item.attrs) =>
+                {
+                    self.add(
+                        "const",
+                        format!("{module_path}::{type_name}::{}", item.ident),
+                        item.ty.to_token_stream().to_string(),
+                    );
+                }
+                ImplItem::Fn(item)
+                    if matches!(item.vis, Visibility::Public(_)) && !is_doc_hidden(&item.attrs) =>
+                {
+                    self.add(
+                        "fn",
+                        format!("{module_path}::{type_name}::{}", item.sig.ident),
+                        item.sig.to_token_stream().to_string(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add(&mut self, kind: &'static str, path: String, signature: String) {
+        self.items.insert(
+            format!("{kind} {path}"),
+            ApiItem {
+                kind,
+                path,
+                signature,
+            },
+        );
+    }
+
+    fn find_module_file(mod_dir: &Path, ident: &syn::Ident) -> Option<PathBuf> {
+        let file_path = mod_dir.join(format!("{ident}.rs"));
+        if file_path.exists() {
+            return Some(file_path);
+        }
+        let file_path = mod_dir.join(ident.to_string()).join("mod.rs");
+        if file_path.exists() {
+            return Some(file_path);
+        }
+        None
+    }
+}
+
+fn is_public(item: &Item) -> bool {
+    let (vis, attrs) = match item {
+        Item::Const(item) => (&item.vis, &item.attrs),
+        Item::Enum(item) => (&item.vis, &item.attrs),
+        Item::Fn(item) => (&item.vis, &item.attrs),
+        Item::Mod(item) => (&item.vis, &item.attrs),
+        Item::Static(item) => (&item.vis, &item.attrs),
+        Item::Struct(item) => (&item.vis, &item.attrs),
+        Item::Trait(item) => (&item.vis, &item.attrs),
+        Item::Type(item) => (&item.vis, &item.attrs),
+        Item::Union(item) => (&item.vis, &item.attrs),
+        Item::Use(item) => (&item.vis, &item.attrs),
+        _ => return false,
+    };
+    matches!(vis, Visibility::Public(_)) && !is_doc_hidden(attrs)
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident(name))
+}
+
+/// Items marked with `#[doc(hidden)]` are not considered as a part of the public API
+fn is_doc_hidden(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if attr.path().is_ident("doc")
+            && let Meta::List(list) = &attr.meta
+            && let Ok(nested) =
+                list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        {
+            nested
+                .iter()
+                .any(|meta| matches!(meta, Meta::Path(path) if path.is_ident("hidden")))
+        } else {
+            false
+        }
+    })
+}
+
+/// Value of the `#[path = "..."]` module attribute
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        if attr.path().is_ident("path")
+            && let Meta::NameValue(name_value) = &attr.meta
+            && let Expr::Lit(expr) = &name_value.value
+            && let Lit::Str(path) = &expr.lit
+        {
+            Some(path.value())
+        } else {
+            None
+        }
+    })
+}
+
+/// Removes attributes and optionally private fields
+fn strip_fields(fields: &mut Fields, remove_private: bool) {
+    match fields {
+        Fields::Named(named) => {
+            named.named = std::mem::take(&mut named.named)
+                .into_iter()
+                .filter(|f| !remove_private || matches!(f.vis, Visibility::Public(_)))
+                .map(|mut f| {
+                    f.attrs.clear();
+                    f
+                })
+                .collect();
+        }
+        Fields::Unnamed(unnamed) => {
+            for field in &mut unnamed.unnamed {
+                field.attrs.clear();
+                if remove_private && !matches!(field.vis, Visibility::Public(_)) {
+                    // keep the position of the private field but hide its type
+                    field.ty = syn::parse_quote!(_);
+                }
+            }
+        }
+        Fields::Unit => {}
+    }
+}
+
+/// Flattens `use` tree into the list of (exported name, source path) pairs
+fn collect_use_tree(tree: &UseTree, prefix: String, reexports: &mut Vec<(String, String)>) {
+    match tree {
+        UseTree::Path(path) => {
+            collect_use_tree(&path.tree, format!("{prefix}{}::", path.ident), reexports)
+        }
+        UseTree::Name(name) => {
+            reexports.push((name.ident.to_string(), format!("{prefix}{}", name.ident)))
+        }
+        UseTree::Rename(rename) => reexports.push((
+            rename.rename.to_string(),
+            format!("{prefix}{}", rename.ident),
+        )),
+        UseTree::Glob(_) => reexports.push((format!("{prefix}*"), format!("{prefix}*"))),
+        UseTree::Group(group) => {
+            for tree in &group.items {
+                collect_use_tree(tree, prefix.clone(), reexports);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use semver::Version;
+
+    use crate::api_comparator::{ApiCollector, is_breaking_update};
+
+    #[test]
+    fn test_breaking_update() {
+        let check = |from: &str, to: &str| {
+            is_breaking_update(&Version::parse(from).unwrap(), &Version::parse(to).unwrap())
+        };
+        assert!(check("1.2.3", "2.0.0"));
+        assert!(!check("1.2.3", "1.3.0"));
+        assert!(!check("1.2.3", "1.2.4"));
+        assert!(check("0.2.3", "0.3.0"));
+        assert!(!check("0.2.3", "0.2.4"));
+        assert!(check("0.0.3", "0.0.4"));
+    }
+
+    #[test]
+    fn test_collect_public_items() {
+        let file = syn::parse_file(
+            r#"
+            pub fn public_fn(a: u32) -> u32 { a }
+            fn private_fn() {}
+            pub struct Foo { pub a: u32, b: String }
+            pub(crate) struct Internal;
+            #[doc(hidden)]
+            pub struct Hidden;
+            #[doc(alias = "hidden_alias")]
+            pub struct Aliased;
+            #[doc(cfg(feature = "x"), hidden)]
+            pub struct HiddenWithCfg;
+            pub mod nested {
+                pub enum Bar { A, B(u32) }
+                pub struct Foo;
+                impl Clone for Foo { fn clone(&self) -> Self { todo!() } }
+                mod private { pub fn not_reachable() {} }
+            }
+            pub use nested::{Bar, Bar as Baz};
+            impl Foo {
+                pub fn new() -> Self { todo!() }
+                fn helper(&self) {}
+            }
+            impl Clone for Foo { fn clone(&self) -> Self { todo!() } }
+            #[macro_export]
+            macro_rules! my_macro { () => {} }
+            "#,
+        )
+        .unwrap();
+
+        let mut collector = ApiCollector::default();
+        collector.collect_items(&file.items, "my_crate", Path::new(""), Path::new(""));
+        let keys: Vec<_> = collector.items.keys().map(|k| k.as_str()).collect();
+
+        assert_eq!(
+            keys,
+            [
+                "enum my_crate::nested::Bar",
+                "fn my_crate::Foo::new",
+                "fn my_crate::public_fn",
+                "impl my_crate::<Foo as Clone>",
+                "impl my_crate::nested::<Foo as Clone>",
+                "macro my_crate::my_macro",
+                "mod my_crate::nested",
+                "struct my_crate::Aliased",
+                "struct my_crate::Foo",
+                "struct my_crate::nested::Foo",
+                "use my_crate::Bar",
+                "use my_crate::Baz",
+            ]
+        );
+        assert_eq!(
+            "pub struct Foo { pub a : u32 }",
+            collector.items["struct my_crate::Foo"].signature
+        );
+    }
+}
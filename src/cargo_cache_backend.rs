@@ -0,0 +1,318 @@
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use semver::Version;
+
+use crate::cargo_runner::CargoRunner;
+use crate::index_cache::IndexCache;
+use crate::registry_backend::{CrateMetadata, RegistryBackend, VersionInfo};
+use crate::registry_source::RegistrySource;
+use crate::stable_hasher::StableHasher;
+
+/// Registry backend on top of the local cargo cache: the index cache and the unpacked registry sources.
+/// Missing crates are downloaded into the cache with the 'cargo info' command.
+pub struct CargoCacheBackend {
+    registry: RegistrySource,
+    /// Registry source folder: `$CARGO_HOME/registry/src/<index host>-<hash>`
+    src_path: PathBuf,
+    /// Registry archives folder: `$CARGO_HOME/registry/cache/<index host>-<hash>`
+    cache_path: PathBuf,
+    /// Registry index folder: `$CARGO_HOME/registry/index/<index host>-<hash>`
+    index_path: PathBuf,
+    /// Use only the local cache
+    offline: bool,
+}
+
+impl CargoCacheBackend {
+    /// `registry_path` is the cargo registry root folder: `$CARGO_HOME/registry`
+    pub fn new(registry_path: &Path, registry: RegistrySource, index: &str, offline: bool) -> Self {
+        let dir_name = find_registry_dir(registry_path, index);
+        Self {
+            registry,
+            src_path: registry_path.join("src").join(&dir_name),
+            cache_path: registry_path.join("cache").join(&dir_name),
+            index_path: registry_path.join("index").join(&dir_name),
+            offline,
+        }
+    }
+
+    /// Versions of the crate unpacked into the local registry
+    fn local_versions(&self, crate_name: &str) -> Vec<VersionInfo> {
+        let prefix = format!("{crate_name}-");
+        let Ok(entries) = std::fs::read_dir(&self.src_path) else {
+            return vec![];
+        };
+        let mut versions: Vec<_> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                Version::parse(file_name.strip_prefix(&prefix)?).ok()
+            })
+            .map(|version| VersionInfo {
+                version,
+                yanked: false,
+                checksum: None,
+            })
+            .collect();
+        versions.sort_by(|a, b| a.version.cmp(&b.version));
+        versions
+    }
+
+    /// Runs 'cargo info' for the specific version of the crate
+    fn cargo_info(&self, crate_name: &str, version: &Version) -> Result<()> {
+        let cargo_runner = CargoRunner::new(None);
+        let mut args = self.registry.cargo_args();
+        args.push(format!("{crate_name}@{version}"));
+        cargo_runner.run("info", args)?;
+        Ok(())
+    }
+}
+
+impl RegistryBackend for CargoCacheBackend {
+    fn list_versions(&self, crate_name: &str) -> Result<Vec<VersionInfo>> {
+        let index_cache = IndexCache::new(self.index_path.clone());
+        let mut entries = index_cache.read(crate_name);
+        if entries.is_none() && !self.offline {
+            self.refresh(crate_name);
+            entries = index_cache.read(crate_name);
+        }
+
+        if let Some(entries) = entries {
+            return Ok(entries
+                .into_iter()
+                .map(|entry| VersionInfo {
+                    version: entry.vers,
+                    yanked: entry.yanked,
+                    checksum: Some(entry.cksum),
+                })
+                .collect());
+        }
+
+        let versions = if self.offline {
+            self.local_versions(crate_name)
+        } else {
+            vec![]
+        };
+        if versions.is_empty() {
+            return Err(anyhow!(
+                "'{crate_name}' crate is not found in the index cache of the '{}' registry",
+                self.registry
+            ));
+        }
+        Ok(versions)
+    }
+
+    /// Runs 'cargo info' that updates the crate entry in the local index cache
+    fn refresh(&self, crate_name: &str) {
+        if self.offline {
+            return;
+        }
+        let cargo_runner = CargoRunner::new(None);
+        let mut args = self.registry.cargo_args();
+        args.push(crate_name.into());
+        if let Err(err) = cargo_runner.run("info", args) {
+            eprintln!("[WARN] Cannot update index cache of the '{crate_name}' crate. Error: {err}");
+        }
+    }
+
+    fn crate_metadata(&self, crate_name: &str, version: &Version) -> Result<CrateMetadata> {
+        let crate_path = match self.source_path(crate_name, version) {
+            Some(crate_path) => crate_path,
+            None => self.download_source(crate_name, version)?,
+        };
+        CrateMetadata::read(&crate_path)
+    }
+
+    fn source_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf> {
+        let crate_path = self.src_path.join(format!("{crate_name}-{version}"));
+        crate_path.exists().then_some(crate_path)
+    }
+
+    /// Runs 'cargo info' for the specific version of the crate to put it into the local registry
+    fn download_source(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        if self.offline {
+            return Err(anyhow!(
+                "'{crate_name}@{version}' crate sources are not in the local registry and cannot be downloaded in offline mode"
+            ));
+        }
+
+        self.cargo_info(crate_name, version)?;
+
+        self.source_path(crate_name, version).ok_or_else(|| {
+            anyhow!(
+                "Cannot find '{crate_name}@{version}' crate sources of the '{}' registry",
+                self.registry
+            )
+        })
+    }
+
+    /// Archive from the registry cache. 'cargo info' puts the missing archive there
+    fn download_archive(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        let archive_path = self
+            .cache_path
+            .join(format!("{crate_name}-{version}.crate"));
+        if archive_path.exists() {
+            return Ok(archive_path);
+        }
+        if self.offline {
+            return Err(anyhow!(
+                "'{crate_name}@{version}' crate archive is not in the local registry cache and cannot be downloaded in offline mode"
+            ));
+        }
+
+        self.cargo_info(crate_name, version)?;
+
+        if archive_path.exists() {
+            Ok(archive_path)
+        } else {
+            Err(anyhow!(
+                "Cannot find '{crate_name}@{version}' crate archive of the '{}' registry",
+                self.registry
+            ))
+        }
+    }
+}
+
+/// Name of the registry folder the same way cargo generates it: `<index host>-<hash>`.
+/// The hash is calculated from the source kind and the index URL.
+pub fn registry_dir_name(index: &str) -> String {
+    // discriminants of cargo `SourceKind` enum
+    const REGISTRY_KIND: u8 = 2;
+    const SPARSE_REGISTRY_KIND: u8 = 3;
+
+    let (kind, url) = if index.starts_with("sparse+") {
+        (SPARSE_REGISTRY_KIND, index)
+    } else {
+        (
+            REGISTRY_KIND,
+            index.strip_prefix("registry+").unwrap_or(index),
+        )
+    };
+    // URL without a path is serialized with the trailing slash
+    let url = match url.split_once("://") {
+        Some((_, rest)) if !rest.contains('/') => format!("{url}/"),
+        _ => url.to_string(),
+    };
+
+    let mut hasher = StableHasher::new();
+    hasher.write_u8(kind);
+    // the same way `str` is hashed
+    hasher.write(url.as_bytes());
+    hasher.write_u8(0xff);
+    let hash = hasher.finish().to_le_bytes();
+    let hash: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+
+    format!("{}-{hash}", index_host(index))
+}
+
+/// Name of the existing registry folder. Cargo before 1.85 used another hash,
+/// so the `<index host>-*` folder is taken if the folder with the computed name doesn't exist
+fn find_registry_dir(registry_path: &Path, index: &str) -> String {
+    let dir_name = registry_dir_name(index);
+    let index_path = registry_path.join("index");
+    if index_path.join(&dir_name).exists() {
+        return dir_name;
+    }
+
+    let prefix = format!("{}-", index_host(index));
+    let Ok(entries) = std::fs::read_dir(&index_path) else {
+        return dir_name;
+    };
+    let mut dir_names: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|file_name| {
+            file_name
+                .strip_prefix(&prefix)
+                .is_some_and(|hash| !hash.is_empty() && !hash.contains('-'))
+        })
+        .collect();
+    dir_names.sort();
+    if dir_names.len() > 1 {
+        eprintln!(
+            "[WARN] Several registry folders match '{index}' index: {}. Using the first one",
+            dir_names.join(", ")
+        );
+    }
+    dir_names.into_iter().next().unwrap_or(dir_name)
+}
+
+/// Host part of the registry index URL
+fn index_host(index: &str) -> &str {
+    let url = index
+        .strip_prefix("sparse+")
+        .or_else(|| index.strip_prefix("registry+"))
+        .unwrap_or(index);
+    let url = url.split_once("://").map(|(_, url)| url).unwrap_or(url);
+    let host = url.split(['/', '?', '#']).next().unwrap_or(url);
+    // skip user info and port
+    let host = host.rsplit_once('@').map(|(_, host)| host).unwrap_or(host);
+    host.split(':').next().unwrap_or(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cargo_cache_backend::{find_registry_dir, index_host, registry_dir_name};
+
+    #[test]
+    fn test_index_host() {
+        assert_eq!(
+            "index.crates.io",
+            index_host("sparse+https://index.crates.io/")
+        );
+        assert_eq!(
+            "github.com",
+            index_host("https://github.com/rust-lang/crates.io-index")
+        );
+        assert_eq!(
+            "my-registry.com",
+            index_host("registry+ssh://git@my-registry.com:2222/index.git")
+        );
+    }
+
+    #[test]
+    fn test_registry_dir_name() {
+        assert_eq!(
+            "index.crates.io-1949cf8c6b5b557f",
+            registry_dir_name("sparse+https://index.crates.io/")
+        );
+        assert_eq!(
+            "github.com-25cdd57fae9f0462",
+            registry_dir_name("https://github.com/rust-lang/crates.io-index")
+        );
+        assert_eq!(
+            "github.com-25cdd57fae9f0462",
+            registry_dir_name("registry+https://github.com/rust-lang/crates.io-index")
+        );
+        assert_eq!(
+            "127.0.0.1-9fcf5ea41d507469",
+            registry_dir_name("http://127.0.0.1:9/git-index")
+        );
+    }
+
+    #[test]
+    fn test_find_registry_dir() {
+        let index = "sparse+https://index.crates.io/";
+        let registry_dir = tempfile::tempdir().unwrap();
+        let index_path = registry_dir.path().join("index");
+        assert_eq!(
+            "index.crates.io-1949cf8c6b5b557f",
+            find_registry_dir(registry_dir.path(), index)
+        );
+
+        // folder of the older cargo
+        std::fs::create_dir_all(index_path.join("index.crates.io-6f17d22bba15001f")).unwrap();
+        std::fs::create_dir_all(index_path.join("github.com-1ecc6299db9ec823")).unwrap();
+        assert_eq!(
+            "index.crates.io-6f17d22bba15001f",
+            find_registry_dir(registry_dir.path(), index)
+        );
+
+        std::fs::create_dir_all(index_path.join("index.crates.io-1949cf8c6b5b557f")).unwrap();
+        assert_eq!(
+            "index.crates.io-1949cf8c6b5b557f",
+            find_registry_dir(registry_dir.path(), index)
+        );
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+
+/// Returns cargo home folder: `$CARGO_HOME` or `$HOME/.cargo`
+pub fn cargo_home() -> Result<PathBuf> {
+    let cargo_home = match std::env::var("CARGO_HOME") {
+        Ok(cargo_home) => cargo_home,
+        Err(_) => format!("{}/.cargo", std::env::var("HOME")?),
+    };
+
+    let cargo_home_path = PathBuf::from(&cargo_home);
+
+    if !std::fs::exists(&cargo_home_path)? {
+        return Err(anyhow!("Cargo path doesn't exist: {cargo_home}"));
+    }
+
+    Ok(cargo_home_path)
+}
+
+/// Name of the crates.io source in the `[source]` configuration section
+pub const CRATES_IO_SOURCE: &str = "crates-io";
+
+/// Source definition from the `[source.<name>]` configuration section
+#[derive(Debug, Default)]
+struct SourceConfig {
+    /// Name of the source that replaces this one
+    replace_with: Option<String>,
+    /// Index URL of the remote registry, e.g. a mirror
+    registry: Option<String>,
+    /// Absolute path of the local registry with the index and `.crate` files
+    local_registry: Option<PathBuf>,
+    /// Absolute path of the directory source, e.g. created by `cargo vendor`
+    directory: Option<PathBuf>,
+}
+
+/// Source that replaces a registry according to the `replace-with` chain of the `[source]` configuration section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplacementSource {
+    /// Remote registry by index URL. Cargo keeps its crates in the local cache under the mirror name
+    Registry(String),
+    /// Local registry folder
+    LocalRegistry(PathBuf),
+    /// Directory with the unpacked crates
+    Directory(PathBuf),
+}
+
+impl std::fmt::Display for ReplacementSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Registry(index) => write!(f, "{index}"),
+            Self::LocalRegistry(path) => write!(f, "local-registry+{}", path.display()),
+            Self::Directory(path) => write!(f, "directory+{}", path.display()),
+        }
+    }
+}
+
+/// Cargo configuration values from the `.cargo/config.toml` files
+#[derive(Debug, Default)]
+pub struct CargoConfig {
+    /// Registry index URLs by registry name
+    registries: HashMap<String, String>,
+    /// Sources by name
+    sources: HashMap<String, SourceConfig>,
+}
+
+impl CargoConfig {
+    /// Loads configuration files from the current folder and its parents and from the cargo home folder.
+    /// Values of the files closer to the current folder have priority.
+    pub fn load(cargo_home: &Path) -> Self {
+        let mut config_paths = vec![cargo_home.join("config.toml"), cargo_home.join("config")];
+        if let Ok(current_dir) = std::env::current_dir() {
+            // parents first so that the nested configs override their values
+            let mut dirs: Vec<_> = current_dir.ancestors().collect();
+            dirs.reverse();
+            for dir in dirs {
+                let cargo_dir = dir.join(".cargo");
+                if cargo_dir != cargo_home {
+                    config_paths.push(cargo_dir.join("config"));
+                    config_paths.push(cargo_dir.join("config.toml"));
+                }
+            }
+        }
+
+        let mut config = Self::default();
+        for config_path in config_paths {
+            if !config_path.is_file() {
+                continue;
+            }
+            match std::fs::read_to_string(&config_path)
+                .map_err(|err| err.to_string())
+                .and_then(|data| data.parse::<toml::Table>().map_err(|err| err.to_string()))
+            {
+                Ok(table) => {
+                    // relative paths are resolved from the folder that contains the '.cargo' folder
+                    let base_dir = config_path
+                        .parent()
+                        .and_then(|p| p.parent())
+                        .unwrap_or(Path::new("."));
+                    config.merge(&table, base_dir);
+                }
+                Err(err) => {
+                    eprintln!("[WARN] Cannot read cargo config {config_path:?}. Error: {err}");
+                }
+            }
+        }
+
+        for source_name in config.sources.keys() {
+            if let Err(err) = config.resolve_replacement(source_name) {
+                eprintln!(
+                    "[WARN] Replacement of the '{source_name}' source is ignored. Error: {err}"
+                );
+            }
+        }
+        config
+    }
+
+    /// Index URL of the registry. `CARGO_REGISTRIES_<NAME>_INDEX` environment variable has priority.
+    pub fn registry_index(&self, name: &str) -> Option<String> {
+        let env_name = format!(
+            "CARGO_REGISTRIES_{}_INDEX",
+            name.to_uppercase().replace('-', "_")
+        );
+        std::env::var(env_name)
+            .ok()
+            .or_else(|| self.registries.get(name).cloned())
+    }
+
+    /// Directory with vendored sources that replaces crates.io
+    pub fn vendor_dir(&self) -> Option<PathBuf> {
+        match self.replacement(CRATES_IO_SOURCE)? {
+            ReplacementSource::Directory(directory) => Some(directory),
+            _ => None,
+        }
+    }
+
+    /// The last source of the `replace-with` chain of the source, e.g. `crates-io` or the registry name.
+    /// Cargo doesn't read the `[source]` section from the environment variables so neither do we
+    pub fn replacement(&self, source_name: &str) -> Option<ReplacementSource> {
+        self.resolve_replacement(source_name).ok().flatten()
+    }
+
+    fn resolve_replacement(&self, source_name: &str) -> Result<Option<ReplacementSource>> {
+        let Some(mut replace_with) = self.replace_with(source_name) else {
+            return Ok(None);
+        };
+        // limit the replacement chain in case of the cycle
+        for _ in 0..=self.sources.len() {
+            let source = self.sources.get(&replace_with).ok_or_else(|| {
+                anyhow!(
+                    "Replacement source '{replace_with}' is not found in the cargo configuration"
+                )
+            })?;
+            if let Some(directory) = &source.directory {
+                return Ok(Some(ReplacementSource::Directory(directory.clone())));
+            }
+            if let Some(local_registry) = &source.local_registry {
+                return Ok(Some(ReplacementSource::LocalRegistry(
+                    local_registry.clone(),
+                )));
+            }
+            match self.replace_with(&replace_with) {
+                Some(next) => replace_with = next,
+                None => return Ok(source.registry.clone().map(ReplacementSource::Registry)),
+            }
+        }
+        Err(anyhow!("Replacement chain contains a cycle"))
+    }
+
+    fn replace_with(&self, source_name: &str) -> Option<String> {
+        self.sources.get(source_name)?.replace_with.clone()
+    }
+
+    fn merge(&mut self, table: &toml::Table, base_dir: &Path) {
+        if let Some(registries) = table.get("registries").and_then(|r| r.as_table()) {
+            for (name, registry) in registries {
+                if let Some(index) = registry.get("index").and_then(|i| i.as_str()) {
+                    self.registries.insert(name.clone(), index.into());
+                }
+            }
+        }
+        if let Some(sources) = table.get("source").and_then(|s| s.as_table()) {
+            for (name, source) in sources {
+                let source_config = self.sources.entry(name.clone()).or_default();
+                if let Some(replace_with) = source.get("replace-with").and_then(|r| r.as_str()) {
+                    source_config.replace_with = Some(replace_with.into());
+                }
+                if let Some(registry) = source.get("registry").and_then(|r| r.as_str()) {
+                    source_config.registry = Some(registry.into());
+                }
+                if let Some(local_registry) = source.get("local-registry").and_then(|l| l.as_str())
+                {
+                    source_config.local_registry = Some(base_dir.join(local_registry));
+                }
+                if let Some(directory) = source.get("directory").and_then(|d| d.as_str()) {
+                    source_config.directory = Some(base_dir.join(directory));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::cargo_config::{CargoConfig, ReplacementSource};
+
+    #[test]
+    fn test_vendor_dir() {
+        let table = r#"
+            [source.crates-io]
+            replace-with = "vendored-sources"
+
+            [source.vendored-sources]
+            directory = "vendor"
+        "#
+        .parse::<toml::Table>()
+        .unwrap();
+
+        let mut config = CargoConfig::default();
+        assert_eq!(None, config.vendor_dir());
+
+        config.merge(&table, Path::new("/work/project"));
+        assert_eq!(
+            Some(PathBuf::from("/work/project/vendor")),
+            config.vendor_dir()
+        );
+    }
+
+    #[test]
+    fn test_replacement() {
+        let table = r#"
+            [source.crates-io]
+            replace-with = "corp-mirror"
+
+            [source.corp-mirror]
+            registry = "sparse+https://mirror.example.com/index/"
+
+            [source.my-registry]
+            replace-with = "offline"
+
+            [source.offline]
+            replace-with = "local"
+
+            [source.local]
+            local-registry = "registry"
+
+            [source.cycle]
+            replace-with = "cycle"
+
+            [source.broken]
+            replace-with = "missing"
+        "#
+        .parse::<toml::Table>()
+        .unwrap();
+
+        let mut config = CargoConfig::default();
+        config.merge(&table, Path::new("/work/project"));
+        assert_eq!(
+            Some(ReplacementSource::Registry(
+                "sparse+https://mirror.example.com/index/".into()
+            )),
+            config.replacement("crates-io")
+        );
+        assert_eq!(None, config.vendor_dir());
+        assert_eq!(
+            Some(ReplacementSource::LocalRegistry(PathBuf::from(
+                "/work/project/registry"
+            ))),
+            config.replacement("my-registry")
+        );
+        assert_eq!(None, config.replacement("corp-mirror"));
+        assert_eq!(None, config.replacement("cycle"));
+        assert_eq!(None, config.replacement("broken"));
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::package_id_info::PackageIdInfo;
+use crate::registry_source::RegistrySource;
+
+#[derive(Debug, Deserialize)]
+struct LockFile {
+    #[serde(default)]
+    package: Vec<LockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockPackage {
+    name: String,
+    version: Version,
+    /// Package source: `registry+<index URL>`, `sparse+<index URL>`, `git+<repository URL>`. Missing for the local packages
+    source: Option<String>,
+    /// SHA-256 checksum of the `.crate` archive of the registry packages
+    checksum: Option<String>,
+}
+
+/// Checksums of the registry packages recorded in the `Cargo.lock`
+#[derive(Debug, Default)]
+pub struct CargoLock {
+    checksums: HashMap<(RegistrySource, String, Version), String>,
+}
+
+impl CargoLock {
+    pub fn read(lock_path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(lock_path)
+            .with_context(|| format!("Cannot read lock file: {lock_path:?}"))?;
+        Self::parse(&data).with_context(|| format!("Cannot parse lock file: {lock_path:?}"))
+    }
+
+    fn parse(data: &str) -> Result<Self> {
+        let lock_file: LockFile = toml::from_str(data)?;
+        let checksums = lock_file
+            .package
+            .into_iter()
+            .filter_map(|package| {
+                let checksum = package.checksum?;
+                let source = package.source?;
+                let (source, path) = source.split_once('+')?;
+                let pkg_info = PackageIdInfo {
+                    source: source.into(),
+                    path: path.into(),
+                    name: package.name,
+                    version: package.version,
+                    commit: None,
+                };
+                let registry = RegistrySource::from_package(&pkg_info);
+                Some(((registry, pkg_info.name, pkg_info.version), checksum))
+            })
+            .collect();
+        Ok(Self { checksums })
+    }
+
+    /// Checksum of the `.crate` archive of the registry package
+    pub fn checksum(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Option<&str> {
+        self.checksums
+            .get(&(registry.clone(), crate_name.into(), version.clone()))
+            .map(|checksum| checksum.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::cargo_lock::CargoLock;
+    use crate::registry_source::RegistrySource;
+
+    #[test]
+    fn test_parse_cargo_lock() {
+        let lock = CargoLock::parse(
+            r#"
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["anyhow"]
+
+[[package]]
+name = "anyhow"
+version = "1.0.99"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "b0674a1ddeecb70197781e945de4b3b8ffb61fa939a5597bcf48503737663100"
+
+[[package]]
+name = "foo"
+version = "0.2.0"
+source = "sparse+https://my-registry.com/index/"
+checksum = "0123"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("b0674a1ddeecb70197781e945de4b3b8ffb61fa939a5597bcf48503737663100"),
+            lock.checksum("anyhow", &Version::new(1, 0, 99), &RegistrySource::CratesIo)
+        );
+        assert_eq!(
+            Some("0123"),
+            lock.checksum(
+                "foo",
+                &Version::new(0, 2, 0),
+                &RegistrySource::Index("sparse+https://my-registry.com/index/".into())
+            )
+        );
+        assert_eq!(
+            None,
+            lock.checksum("foo", &Version::new(0, 2, 0), &RegistrySource::CratesIo)
+        );
+        assert_eq!(
+            None,
+            lock.checksum("app", &Version::new(0, 1, 0), &RegistrySource::CratesIo)
+        );
+    }
+}
@@ -2,8 +2,9 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use cargo_metadata::{Metadata, MetadataCommand};
+use cargo_metadata::{Metadata, MetadataCommand, PackageId};
 
+use crate::crate_files::copy_crate_files;
 use crate::package_id_info::PackageIdInfo;
 use crate::package_source::PackageSource;
 
@@ -13,36 +14,45 @@ pub struct CargoMeta {
 }
 
 impl CargoMeta {
-    pub fn new(registry_path: &Path) -> Result<Self> {
+    /// Runs `cargo metadata` for the manifest. In offline mode cargo doesn't access the network
+    pub fn new(registry_path: &Path, offline: bool) -> Result<Self> {
         let manifest_path = if registry_path.ends_with("Cargo.toml") {
             PathBuf::from(registry_path)
         } else {
             registry_path.join("Cargo.toml")
         };
-        let metadata = MetadataCommand::new()
-            .manifest_path(manifest_path)
+        let mut command = MetadataCommand::new();
+        command.manifest_path(manifest_path);
+        if offline {
+            command.other_options(vec!["--offline".to_string()]);
+        }
+        let metadata = command
             .exec()
             .context("Cannot get metadata for manifest: {manifest_path}")?;
         Ok(Self { metadata })
     }
 
-    /// Try to extract registry path from the metadata
-    pub fn registry_path(&self) -> Option<PathBuf> {
-        self.metadata.packages.iter().find_map(|pkg| {
-            let registry_path = pkg.manifest_path.to_string();
-            if registry_path.contains(".cargo/") {
-                PathBuf::from(registry_path)
-                    .parent()
-                    .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-                    .filter(|p| {
-                        p.to_str()
-                            .map(|s| s.contains(".cargo/registry/src/index.crates.io-"))
-                            == Some(true)
-                    })
-            } else {
-                None
-            }
-        })
+    /// Runs `cargo metadata` for the private copy of the crate so the generated `Cargo.lock` never gets into
+    /// the shared cargo caches. `source_root` is the copied folder that contains the crate, e.g. the git checkout
+    pub fn from_copy(
+        crate_path: &Path,
+        source_root: &Path,
+        git_checkout: bool,
+        offline: bool,
+    ) -> Result<Self> {
+        let copy_dir = tempfile::Builder::new()
+            .prefix("cargo-ddd-metadata-")
+            .tempdir()?;
+        copy_crate_files(source_root, copy_dir.path(), git_checkout)?;
+        Self::new(
+            &copy_dir.path().join(crate_path.strip_prefix(source_root)?),
+            offline,
+        )
+    }
+
+    /// Root folder of the workspace
+    pub fn workspace_root(&self) -> &Path {
+        self.metadata.workspace_root.as_std_path()
     }
 
     /// Returns all entries of the crate in all workspace targets
@@ -64,14 +74,14 @@ impl CargoMeta {
             let mut packages = HashMap::new();
             for node in &resolve.nodes {
                 if workspace_members.contains(&node.id) {
-                    let Some(info) = PackageIdInfo::from_package_id(&node.id) else {
+                    let Some(info) = self.package_info(&node.id) else {
                         continue;
                     };
 
                     let mut deps = vec![];
                     for dep in &node.dependencies {
                         // choose only non-local dependencies
-                        if let Some(pkg_dep) = PackageIdInfo::from_package_id(dep)
+                        if let Some(pkg_dep) = self.package_info(dep)
                             && pkg_dep.source != PackageSource::Path
                         {
                             deps.push(pkg_dep);
@@ -124,7 +134,7 @@ impl CargoMeta {
                         continue 'next_node;
                     }
                 }
-                if let Some(pkg_info) = PackageIdInfo::from_package_id(&node.id) {
+                if let Some(pkg_info) = self.package_info(&node.id) {
                     packages.push(pkg_info)
                 }
             }
@@ -137,4 +147,20 @@ impl CargoMeta {
             vec![]
         }
     }
+
+    /// Package info with the commit of the git package from its source: `git+<url>?<reference>#<commit>`
+    fn package_info(&self, pkg_id: &PackageId) -> Option<PackageIdInfo> {
+        let mut info = PackageIdInfo::from_package_id(pkg_id)?;
+        if info.source == PackageSource::Git {
+            info.commit = self
+                .metadata
+                .packages
+                .iter()
+                .find(|p| p.id == *pkg_id)
+                .and_then(|p| p.source.as_ref())
+                .and_then(|source| source.repr.rsplit_once('#'))
+                .map(|(_, commit)| commit.into());
+        }
+        Some(info)
+    }
 }
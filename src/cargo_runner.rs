@@ -5,6 +5,8 @@ use anyhow::{Context, Result, anyhow};
 /// Wrapper for cargo subcommands
 pub struct CargoRunner {
     cargo_path: PathBuf,
+    /// Environment variables of the cargo process
+    envs: Vec<(String, String)>,
 }
 
 impl CargoRunner {
@@ -14,7 +16,16 @@ impl CargoRunner {
             .or_else(|| std::env::var("CARGO").map(PathBuf::from).ok())
             .unwrap_or_else(|| PathBuf::from("cargo"));
 
-        Self { cargo_path }
+        Self {
+            cargo_path,
+            envs: vec![],
+        }
+    }
+
+    /// Sets environment variable of the cargo process
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
     }
 
     /// Runs 'cargo <subcommand> <args>' command and returns its output
@@ -26,6 +37,7 @@ impl CargoRunner {
         let mut cmd = Command::new(&self.cargo_path);
         cmd.arg(subcommand);
         cmd.args(args);
+        cmd.envs(self.envs.iter().map(|(key, value)| (key, value)));
 
         let output = cmd.output()?;
         if !output.status.success() {
@@ -0,0 +1,56 @@
+use std::collections::BTreeSet;
+
+use semver::Version;
+
+use crate::crate_archive::ChecksumMismatch;
+use crate::diff_report::DiffReport;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// Verifies checksums of the `.crate` archives of the changed crates
+pub struct ChecksumVerifier<'a> {
+    registry_manager: &'a RegistryManager,
+}
+
+impl<'a> ChecksumVerifier<'a> {
+    pub fn new(registry_manager: &'a RegistryManager) -> Self {
+        Self { registry_manager }
+    }
+
+    /// Returns checksum mismatches of both versions of all changed crates in the report
+    pub fn verify(&self, report: &DiffReport) -> Vec<ChecksumMismatch> {
+        let mut verified = BTreeSet::new();
+        let mut mismatches = vec![];
+
+        for diff in report.crate_diffs() {
+            for version in [&diff.from_version, &diff.to_version].into_iter().flatten() {
+                if !verified.insert((&diff.registry, &diff.name, version)) {
+                    continue;
+                }
+                mismatches.extend(self.verify_crate(&diff.name, version, &diff.registry));
+            }
+        }
+
+        mismatches
+    }
+
+    fn verify_crate(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Vec<ChecksumMismatch> {
+        match self
+            .registry_manager
+            .verify_crate(crate_name, version, registry)
+        {
+            Ok(mismatches) => mismatches,
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot verify checksum of the '{crate_name}@{version}' crate. Error: {err:#}"
+                );
+                vec![]
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@ use clap::Parser;
 use semver::Version;
 
 use crate::crate_diff_request::CrateDiffRequest;
+use crate::crates_io_backend::CRATES_IO_API_URL;
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -28,6 +29,77 @@ pub struct Cli {
     /// Generate diff links for diff.rs site instead of original one
     #[arg(short, long)]
     pub diff_rs: bool,
+    /// Compare licenses of the updated crates and show licenses of the added ones
+    #[arg(short, long)]
+    pub licenses: bool,
+    /// Comma separated list of allowed SPDX licenses. Fail if a crate license leaves it. Implies `--licenses`
+    #[arg(long, value_delimiter = ',')]
+    pub allowed_licenses: Vec<String>,
+    /// Compare sources of the updated crates and show changed files statistics
+    #[arg(short, long)]
+    pub stats: bool,
+    /// Show unified diffs of the changed source files. Implies `--stats`
+    #[arg(long)]
+    pub source_diff: bool,
+    /// Glob pattern of the source files to compare, e.g. 'src/**'. Can be repeated
+    #[arg(long)]
+    pub include: Vec<String>,
+    /// Glob pattern of the source files to ignore, e.g. 'tests/**'. Can be repeated
+    #[arg(long)]
+    pub exclude: Vec<String>,
+    /// Compare public API of the updated crates and flag suspected semver violations
+    #[arg(long)]
+    pub api: bool,
+    /// Check packages of the updated and added crates for symlinks, hidden files, suspicious names etc.
+    #[arg(long)]
+    pub anomalies: bool,
+    /// Show unpacked size and file count changes of the changed crates
+    #[arg(long)]
+    pub size: bool,
+    /// Mark crates whose size grows by more than this percent. Implies `--size`
+    #[arg(long)]
+    pub size_threshold: Option<f64>,
+    /// Path to the configuration file [default: .cargo-ddd.toml next to the Cargo.toml]
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    /// Registry name from the cargo configuration for the explicitly specified crates
+    #[arg(long)]
+    pub registry: Option<String>,
+    /// Vendor directory with the current crate sources [default: from the cargo source replacement configuration]
+    #[arg(long)]
+    pub vendor_dir: Option<PathBuf>,
+    /// Vendor directory with the candidate crate sources to compare with the current vendor directory
+    #[arg(long)]
+    pub candidate_vendor_dir: Option<PathBuf>,
+    /// Use only the local registry and index cache. Never access the network
+    #[arg(long)]
+    pub offline: bool,
+    /// Use crates.io compatible HTTP API with the base URL instead of the local cargo cache for crates.io crates
+    #[arg(
+        long,
+        value_name = "URL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = CRATES_IO_API_URL,
+        conflicts_with = "offline"
+    )]
+    pub crates_io_api: Option<String>,
+    /// Number of crates resolved in parallel [default: number of CPUs]
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+    /// Do not use the on-disk cache of the crates info, commit hashes and nested packages
+    #[arg(long)]
+    pub no_cache: bool,
+    /// Ignore the on-disk cache entries and update them
+    #[arg(long, conflicts_with = "no_cache")]
+    pub refresh: bool,
+    /// Allow pre-release versions (alpha, beta, rc etc.) when the latest crate version is picked
+    #[arg(long)]
+    pub pre: bool,
+    /// Verify SHA-256 of the `.crate` archives against the `Cargo.lock` and the registry index.
+    /// Crate sources are unpacked from the verified archives only
+    #[arg(long)]
+    pub verify_checksums: bool,
     /// List of crates with optional versions to inspect
     #[arg(value_parser = parse_crate_diff_info)]
     pub crates: Vec<CrateDiffRequest>,
@@ -47,54 +119,53 @@ fn parse_crate_diff_info(s: &str) -> Result<CrateDiffRequest, String> {
 
     if parts.len() == 2 {
         // crate name and version definition: ddd serde@...
-        let version_parts: Vec<_> = parts[1].split('-').collect();
-        if version_parts.len() == 1 {
-            // just single version or no version:
-            //   ddd serde@1.0.223  # same as: ddd serde@-1.0.223
-            //   ddd serde@         # same as: ddd serde
-            let to_version = if version_parts[0].trim().is_empty() {
-                None // ddd serde@
-            } else {
-                // ddd serde@1.0.223
-                Some(Version::parse(version_parts[0]).map_err(|err| err.to_string())?)
-            };
-
-            return Ok(CrateDiffRequest {
-                crate_name: parts[0].into(),
-                from_version: None,
-                to_version,
-            });
-        }
-
-        if version_parts.len() == 2 {
-            // from/to version range:
-            //   ddd serde@1.0.223-1.0.226
-            //   ddd serde@1.0.223-           # diff from 1.0.223 to the latest
-            //   ddd serde@-1.0.223           # diff of the current crate serde version to 1.0.223
-            //   ddd serde@-                  # same as: ddd serde
-            let from_version = if version_parts[0].trim().is_empty() {
-                None //   ddd serde@-...
-            } else {
-                //   ddd serde@1.0.223-...
-                Some(Version::parse(version_parts[0]).map_err(|err| err.to_string())?)
-            };
-            let to_version = if version_parts[1].trim().is_empty() {
-                None //   ddd serde@...-
-            } else {
-                //   ddd serde@...-1.0.223
-                Some(Version::parse(version_parts[1]).map_err(|err| err.to_string())?)
-            };
-
-            return Ok(CrateDiffRequest {
-                crate_name: parts[0].into(),
-                from_version,
-                to_version,
-            });
-        }
+        let (from_version, to_version) = parse_version_range(parts[1])?;
+        return Ok(CrateDiffRequest {
+            crate_name: parts[0].into(),
+            from_version,
+            to_version,
+        });
     }
     Err(format!("Wrong crate version format: {s}"))
 }
 
+/// Parses optional from/to versions. Pre-release versions contain `-` too, so every `-` is tried as the range separator:
+///   ddd serde@1.0.223-1.0.226       # from/to version range
+///   ddd serde@1.0.223-              # diff from 1.0.223 to the latest
+///   ddd serde@-1.0.223              # diff of the current crate serde version to 1.0.223
+///   ddd serde@1.0.0-rc.1-1.0.0      # pre-release versions in the range
+///   ddd serde@-                     # same as: ddd serde
+/// If no `-` splits the definition into 2 versions it's a single version:
+///   ddd serde@1.0.223               # same as: ddd serde@-1.0.223
+///   ddd serde@1.0.0-rc.1            # same as: ddd serde@-1.0.0-rc.1
+///   ddd serde@                      # same as: ddd serde
+fn parse_version_range(s: &str) -> Result<(Option<Version>, Option<Version>), String> {
+    let mut ranges: Vec<_> = s
+        .match_indices('-')
+        .filter_map(|(index, _)| {
+            let from_version = parse_optional_version(&s[..index]).ok()?;
+            let to_version = parse_optional_version(&s[index + 1..]).ok()?;
+            Some((from_version, to_version))
+        })
+        .collect();
+
+    match ranges.len() {
+        0 => Ok((None, parse_optional_version(s)?)),
+        1 => Ok(ranges.remove(0)),
+        _ => Err(format!(
+            "Ambiguous crate version range: {s}. It can be split into versions in {} ways",
+            ranges.len()
+        )),
+    }
+}
+
+fn parse_optional_version(s: &str) -> Result<Option<Version>, String> {
+    if s.trim().is_empty() {
+        return Ok(None);
+    }
+    Version::parse(s).map(Some).map_err(|err| err.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use semver::Version;
@@ -131,6 +202,45 @@ mod tests {
         assert_eq!(Some(Version::new(1, 0, 228)), info.to_version);
     }
 
+    #[test]
+    fn test_pre_release_versions() {
+        let rc = Version::parse("1.0.0-rc.1").unwrap();
+        let build = Version::parse("1.0.0+build-5").unwrap();
+        for (s, from_version, to_version) in [
+            ("serde@1.0.0-rc.1", None, Some(rc.clone())),
+            ("serde@-1.0.0-rc.1", None, Some(rc.clone())),
+            ("serde@1.0.0-rc.1-", Some(rc.clone()), None),
+            (
+                "serde@1.0.0-rc.1-1.0.0",
+                Some(rc.clone()),
+                Some(Version::new(1, 0, 0)),
+            ),
+            (
+                "serde@0.9.0-1.0.0-rc.1",
+                Some(Version::new(0, 9, 0)),
+                Some(rc.clone()),
+            ),
+            ("serde@1.0.0+build-5", None, Some(build.clone())),
+            (
+                "serde@1.0.0+build-5-1.0.1",
+                Some(build),
+                Some(Version::new(1, 0, 1)),
+            ),
+        ] {
+            let info = parse_crate_diff_info(s).expect("Wrong crate version definition");
+            assert_eq!("serde", info.crate_name, "{s}");
+            assert_eq!(from_version, info.from_version, "{s}");
+            assert_eq!(to_version, info.to_version, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_ambiguous_range_fail() {
+        // 1.0.0 -> 1.0.1-1.0.2 or 1.0.0-1.0.1 -> 1.0.2
+        let result = parse_crate_diff_info("serde@1.0.0-1.0.1-1.0.2");
+        assert!(result.unwrap_err().contains("Ambiguous"));
+    }
+
     #[test]
     fn test_multiple_at_fail() {
         let result = parse_crate_diff_info("serde@1.0.224@1.0.228");
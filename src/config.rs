@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::forge::ForgeTemplates;
+use crate::path_filter::PathFilter;
+
+/// Default configuration file name. It's searched next to the `Cargo.toml`
+pub const CONFIG_FILE_NAME: &str = ".cargo-ddd.toml";
+
+/// cargo-ddd configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Config {
+    /// Source file filters applied to all crates
+    #[serde(default)]
+    pub filter: PathFilter,
+    /// Package size growth in percents after which the crate is marked in the size report
+    pub size_threshold: Option<f64>,
+    /// Per-crate settings
+    #[serde(default)]
+    pub crates: HashMap<String, CrateConfig>,
+    /// Commit and compare link templates of the self-hosted forges by host
+    #[serde(default)]
+    pub forges: HashMap<String, ForgeTemplates>,
+}
+
+/// Crate specific settings
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CrateConfig {
+    /// Source file filters of the crate. Include patterns replace the global ones, exclude patterns are added to them
+    #[serde(default)]
+    pub filter: PathFilter,
+}
+
+impl Config {
+    /// Loads configuration from the explicit path or from the default file next to the manifest.
+    /// Returns default configuration if the default file doesn't exist.
+    pub fn load(config_path: Option<&Path>, manifest_path: &Path) -> Result<Self> {
+        let config_path = match config_path {
+            Some(config_path) => config_path.to_path_buf(),
+            None => {
+                let config_path = Self::default_path(manifest_path);
+                if !config_path.exists() {
+                    return Ok(Self::default());
+                }
+                config_path
+            }
+        };
+
+        let config_data = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Cannot read config file: {config_path:?}"))?;
+        toml::from_str(&config_data)
+            .with_context(|| format!("Cannot parse config file: {config_path:?}"))
+    }
+
+    /// Returns source file filter of the crate combined with the global one.
+    /// Crate include patterns narrow the global ones, so they replace them
+    pub fn crate_filter(&self, crate_name: &str) -> PathFilter {
+        let mut filter = self.filter.clone();
+        if let Some(crate_config) = self.crates.get(crate_name) {
+            if !crate_config.filter.include.is_empty() {
+                filter.include = crate_config.filter.include.clone();
+            }
+            filter
+                .exclude
+                .extend(crate_config.filter.exclude.iter().cloned());
+        }
+        filter
+    }
+
+    fn default_path(manifest_path: &Path) -> PathBuf {
+        let manifest_dir = if manifest_path.ends_with("Cargo.toml") {
+            manifest_path.parent().unwrap_or(Path::new("."))
+        } else {
+            manifest_path
+        };
+        manifest_dir.join(CONFIG_FILE_NAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+
+    #[test]
+    fn test_crate_filter() {
+        let config: Config = toml::from_str(
+            r#"
+            [filter]
+            include = ["src/**", "build.rs"]
+            exclude = ["*.snap"]
+
+            [crates.foo.filter]
+            include = ["src/parser/**"]
+
+            [crates.bar.filter]
+            exclude = ["src/generated/**"]
+            "#,
+        )
+        .unwrap();
+
+        let foo_filter = config.crate_filter("foo");
+        assert!(foo_filter.matches("src/parser/mod.rs"));
+        assert!(!foo_filter.matches("src/lib.rs"));
+        assert!(!foo_filter.matches("build.rs"));
+        assert!(!foo_filter.matches("src/parser/test.snap"));
+
+        let bar_filter = config.crate_filter("bar");
+        assert!(bar_filter.matches("src/lib.rs"));
+        assert!(bar_filter.matches("build.rs"));
+        assert!(!bar_filter.matches("src/generated/types.rs"));
+        assert!(!bar_filter.matches("src/test.snap"));
+
+        assert!(config.crate_filter("baz").matches("src/lib.rs"));
+        assert!(!config.crate_filter("baz").matches("tests/it.rs"));
+    }
+}
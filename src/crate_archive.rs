@@ -0,0 +1,170 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use flate2::read::GzDecoder;
+use semver::Version;
+use sha2::{Digest, Sha256};
+
+/// Marker file that is created after the crate is completely unpacked
+pub const UNPACKED_MARKER_FILE: &str = ".cargo-ok";
+
+/// Where the expected checksum of the `.crate` archive comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumSource {
+    /// `checksum` field of the `Cargo.lock` package
+    Lockfile,
+    /// `cksum` field of the registry index entry
+    Index,
+    /// Neither the `Cargo.lock` nor the registry index has the checksum. The archive cannot be trusted
+    Missing,
+    /// `package` checksum of the same crate version in the current vendor directory
+    Vendor,
+}
+
+impl ChecksumSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Lockfile => "Cargo.lock",
+            Self::Index => "index",
+            Self::Missing => "no checksum",
+            Self::Vendor => "current vendor directory",
+        }
+    }
+}
+
+/// SHA-256 of the `.crate` archive differs from the expected one or there is no expected one
+#[derive(Debug, Clone)]
+pub struct ChecksumMismatch {
+    /// Crate name
+    pub name: String,
+    pub version: Version,
+    pub source: ChecksumSource,
+    /// Expected checksum. Empty if the checksum is missing
+    pub expected: String,
+    pub actual: String,
+    /// Path to the `.crate` archive or to the candidate vendored crate
+    pub archive: PathBuf,
+}
+
+/// Hex encoded SHA-256 of the file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Cannot open file: {path:?}"))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let count = file.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        hasher.update(&buffer[..count]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
+
+/// Unpacks `.crate` archive into the `<dest_path>/<crate_dir>` folder.
+/// All archive entries must be inside of the `crate_dir` folder.
+pub fn unpack_crate(archive: impl Read, dest_path: &Path, crate_dir: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dest_path)?;
+    // unpack into the private folder first so the concurrent readers never see a partially unpacked crate
+    let unpack_dir = tempfile::Builder::new()
+        .prefix(".unpack-")
+        .tempdir_in(dest_path)?;
+
+    let mut archive = tar::Archive::new(GzDecoder::new(archive));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?.into_owned();
+        if !entry_path.starts_with(crate_dir) {
+            return Err(anyhow!(
+                "Archive entry is outside of the crate folder: {entry_path:?}"
+            ));
+        }
+        entry.unpack_in(unpack_dir.path())?;
+    }
+
+    let unpacked_path = unpack_dir.path().join(crate_dir);
+    std::fs::create_dir_all(&unpacked_path)?;
+    std::fs::write(unpacked_path.join(UNPACKED_MARKER_FILE), "{\"v\":1}")?;
+    move_unpacked(&unpacked_path, &dest_path.join(crate_dir))
+}
+
+/// Moves the completely unpacked folder to its final location.
+/// Succeeds if the same folder was concurrently moved there already.
+pub fn move_unpacked(unpacked_path: &Path, target_path: &Path) -> Result<PathBuf> {
+    if target_path.exists() && !target_path.join(UNPACKED_MARKER_FILE).exists() {
+        // leftovers of the interrupted unpacking
+        std::fs::remove_dir_all(target_path)?;
+    }
+    if let Err(err) = std::fs::rename(unpacked_path, target_path)
+        && !target_path.join(UNPACKED_MARKER_FILE).exists()
+    {
+        return Err(err.into());
+    }
+    Ok(target_path.into())
+}
+
+/// Path to the completely unpacked crate folder
+pub fn unpacked_crate(dest_path: &Path, crate_dir: &str) -> Option<PathBuf> {
+    let crate_path = dest_path.join(crate_dir);
+    crate_path
+        .join(UNPACKED_MARKER_FILE)
+        .exists()
+        .then_some(crate_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    use crate::crate_archive::{sha256_file, unpack_crate, unpacked_crate};
+
+    #[test]
+    fn test_unpack_crate() {
+        let dest_dir = tempfile::tempdir().unwrap();
+        let dest_path = dest_dir.path();
+        let archive_path = dest_path.join("foo-1.0.0.crate");
+        std::fs::write(&archive_path, "abc").unwrap();
+        let hash = sha256_file(&archive_path);
+
+        let archive = |path: &str| {
+            let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+            let mut header = tar::Header::new_gnu();
+            header.set_size(0);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, &[][..]).unwrap();
+            builder.into_inner().unwrap().finish().unwrap()
+        };
+        let unpacked = unpack_crate(
+            archive("foo-1.0.0/Cargo.toml").as_slice(),
+            dest_path,
+            "foo-1.0.0",
+        );
+        let found = unpacked_crate(dest_path, "foo-1.0.0");
+        let escaped = unpack_crate(
+            archive("bar-1.0.0/Cargo.toml").as_slice(),
+            dest_path,
+            "foo-1.0.1",
+        );
+
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            hash.unwrap()
+        );
+        assert!(
+            unpacked
+                .unwrap()
+                .join("Cargo.toml")
+                .ends_with("foo-1.0.0/Cargo.toml")
+        );
+        assert!(found.is_some());
+        assert!(escaped.is_err());
+    }
+}
@@ -1,131 +1,291 @@
+use std::cmp::Ordering;
+
 use indexmap::IndexMap;
 use semver::Version;
 
 use crate::cargo_meta::CargoMeta;
+use crate::crate_archive::UNPACKED_MARKER_FILE;
 use crate::crate_diff_info::CrateDiffInfo;
 use crate::crate_diff_request::CrateDiffRequest;
+use crate::crate_info::CrateInfo;
+use crate::crate_prefetch::PrefetchCrate;
+use crate::dependency_diff::DependencyDiff;
+use crate::diff_report::DiffReport;
+use crate::git_source::GitSource;
 use crate::package_id_info::PackageIdInfo;
+use crate::persistent_cache::{CacheKind, LATEST_TTL};
 use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+use crate::vcs_info::VcsInfo;
+use crate::vendor_dir::{VendorCrate, VendorDir};
+use crate::worker_pool::WorkerPool;
+
+/// Change of the nested dependency between 2 versions of the crate
+enum NestedChange {
+    Removed(PackageIdInfo),
+    Added(PackageIdInfo),
+    Updated(PackageIdInfo, PackageIdInfo),
+}
+
+impl NestedChange {
+    fn packages(&self) -> Vec<&PackageIdInfo> {
+        match self {
+            Self::Removed(pkg) | Self::Added(pkg) => vec![pkg],
+            Self::Updated(from_pkg, to_pkg) => vec![from_pkg, to_pkg],
+        }
+    }
+}
 
 /// Generates diff information for the crate 2 versions
 pub struct CrateDiffBuilder {
     registry_manager: RegistryManager,
+    /// Registry of the explicitly specified crates from the command line
+    registry: Option<String>,
     diff_rs: bool,
+    /// Resolves crates in parallel
+    worker_pool: WorkerPool,
 }
 
 impl CrateDiffBuilder {
-    pub fn new(registry_manager: RegistryManager, diff_rs: bool) -> Self {
+    pub fn new(
+        registry_manager: RegistryManager,
+        registry: Option<String>,
+        diff_rs: bool,
+        jobs: usize,
+    ) -> Self {
         Self {
             registry_manager,
+            registry,
             diff_rs,
+            worker_pool: WorkerPool::new(jobs),
         }
     }
 
+    pub fn registry_manager(&self) -> &RegistryManager {
+        &self.registry_manager
+    }
+
     /// Build diffs for the dependencies of the current crate that require update.
     /// Called when no crates are provided in the command line:
     ///   ddd
-    pub fn build_from_crate(
-        &mut self,
-        cargo_meta: &CargoMeta,
-    ) -> IndexMap<String, Vec<CrateDiffInfo>> {
-        let mut target_version_diffs = IndexMap::new();
-        let dependencies = cargo_meta.workspace_member_dependencies();
-
-        for (target_name, deps) in dependencies {
-            let mut diff_info = vec![];
-            for dep in deps {
-                let info = self.registry_manager.get_crate_info(&dep.name, None);
-
-                if self.diff_rs {
-                    if let Some(latest_version) = &info.version
-                        && dep.version == *latest_version
-                    {
-                        continue;
-                    }
-                    diff_info.push(CrateDiffInfo {
-                        name: dep.name,
-                        from_version: Some(dep.version),
-                        from_hash: None,
-                        to_version: info.version,
-                        to_hash: None,
-                        repository: None,
-                    });
-                } else if let Some(latest_version) = info.version {
-                    if dep.version != latest_version {
-                        let from_hash = self.registry_manager.get_pkg_hash(&dep);
-                        let to_hash = self
-                            .registry_manager
-                            .get_crate_hash(&dep.name, &latest_version);
-
-                        diff_info.push(CrateDiffInfo {
-                            name: dep.name,
-                            from_version: Some(dep.version),
-                            from_hash,
-                            to_version: Some(latest_version),
-                            to_hash,
-                            repository: info.repository, // TODO: can repository of the same crate to change between versions?
-                        });
-                    }
+    pub fn build_from_crate(&self, cargo_meta: &CargoMeta) -> IndexMap<String, Vec<CrateDiffInfo>> {
+        let mut target_version_diffs: IndexMap<String, Vec<CrateDiffInfo>> = IndexMap::new();
+        let mut target_deps = vec![];
+        for (target_name, deps) in cargo_meta.workspace_member_dependencies() {
+            target_version_diffs.insert(target_name.clone(), vec![]);
+            target_deps.extend(deps.into_iter().map(|dep| (target_name.clone(), dep)));
+        }
+
+        let target_deps: Vec<_> = target_deps
+            .into_iter()
+            .map(|(target_name, dep)| {
+                let registry = RegistrySource::from_package(&dep);
+                (target_name, dep, registry)
+            })
+            .collect();
+        // latest versions are resolved from the index, so both versions are downloaded in batches
+        // before their manifests are read
+        let latest_versions = self.latest_versions(
+            target_deps
+                .iter()
+                .map(|(_, dep, registry)| (dep.name.clone(), registry.clone()))
+                .collect(),
+        );
+        self.registry_manager.prefetch(
+            target_deps
+                .iter()
+                .zip(&latest_versions)
+                .filter(|((_, _, registry), _)| !matches!(registry, RegistrySource::Git(_)))
+                .flat_map(|((_, dep, registry), latest_version)| {
+                    [Some(&dep.version), latest_version.as_ref()]
+                        .into_iter()
+                        .flatten()
+                        .map(|version| PrefetchCrate::new(&dep.name, version, registry))
+                }),
+        );
+
+        let diffs = self
+            .worker_pool
+            .map(target_deps, |(target_name, dep, registry)| {
+                let info = if let RegistrySource::Git(_) = registry {
+                    None
                 } else {
-                    let from_hash = self.registry_manager.get_pkg_hash(&dep);
-
-                    diff_info.push(CrateDiffInfo {
-                        name: dep.name,
-                        from_version: Some(dep.version),
-                        from_hash,
-                        to_version: None,
-                        to_hash: None,
-                        repository: info.repository, // TODO: can repository of the same crate to change between versions?
-                    });
-                }
+                    Some(
+                        self.registry_manager
+                            .get_crate_info(&dep.name, None, &registry),
+                    )
+                };
+                (target_name, self.build_dependency_diff(dep, registry, info))
+            });
+        for (target_name, diff) in diffs {
+            if let Some(diff) = diff {
+                target_version_diffs
+                    .entry(target_name)
+                    .or_default()
+                    .push(diff);
             }
-            target_version_diffs.insert(target_name, diff_info);
         }
         target_version_diffs
     }
 
+    /// Diff between the current version of the dependency and the latest one.
+    /// `info` is the latest crate info. Git crates don't have it
+    fn build_dependency_diff(
+        &self,
+        dep: PackageIdInfo,
+        registry: RegistrySource,
+        info: Option<CrateInfo>,
+    ) -> Option<CrateDiffInfo> {
+        if let RegistrySource::Git(source) = registry {
+            let from_hash = self.registry_manager.get_pkg_hash(&dep);
+            return self.build_git_diff(&dep.name, dep.version, from_hash, source);
+        }
+        let info = info?;
+
+        if up_to_date(&dep.version, info.version.as_ref()) {
+            return None;
+        }
+        let from_info = self.version_info(&dep.name, Some(dep.version.clone()), &registry);
+        if self.diff_rs {
+            return self.build_diff(&dep.name, from_info, info, registry);
+        }
+
+        let from_vcs = self.registry_manager.get_pkg_vcs_info(&dep);
+        let to_vcs = info.version.as_ref().and_then(|latest_version| {
+            self.registry_manager
+                .get_vcs_info(&dep.name, latest_version, &registry)
+        });
+        let from_yanked = self.is_yanked(&dep.name, Some(&dep.version), &registry);
+        let to_yanked = self.is_yanked(&dep.name, info.version.as_ref(), &registry);
+        Some(CrateDiffInfo {
+            name: dep.name,
+            from_version: Some(dep.version),
+            from_hash: vcs_hash(&from_vcs),
+            to_version: info.version,
+            to_hash: vcs_hash(&to_vcs),
+            from_yanked,
+            to_yanked,
+            repository: info
+                .repository
+                .clone()
+                .or_else(|| from_info.repository.clone()),
+            from_repository: from_info.repository,
+            to_repository: info.repository,
+            from_homepage: from_info.homepage,
+            to_homepage: info.homepage,
+            path_in_vcs: path_in_vcs(
+                &from_vcs,
+                &to_vcs,
+                &from_info.repository_subpath,
+                &info.repository_subpath,
+            ),
+            registry,
+        })
+    }
+
     /// Build diffs for requested dependencies.
     /// Called when crates are provided in the command line and they require current crate info:
     ///   ddd serde@-1.0.223
     pub fn build_from_crate_deps(
-        &mut self,
+        &self,
         crates: &[CrateDiffRequest],
         cargo_meta: &CargoMeta,
     ) -> IndexMap<String, Vec<CrateDiffInfo>> {
-        let mut target_version_diffs = IndexMap::new();
+        let mut requests = vec![];
         for pkg in crates {
+            let explicit_registry = self.explicit_registry();
             let mut from_versions = match &pkg.from_version {
-                Some(version) => IndexMap::from([("".to_string(), Some(version.clone()))]),
+                Some(version) => IndexMap::from([(
+                    "".to_string(),
+                    (Some(version.clone()), None, explicit_registry),
+                )]),
                 None => {
-                    // Take crate versions from Cargo.toml
+                    // Take crate versions and registries from Cargo.toml unless registry is specified explicitly
                     cargo_meta
                         .get_dependency_info(&pkg.crate_name)
                         .into_iter()
-                        .map(|(name, info)| (name, Some(info.version)))
+                        .map(|(name, info)| {
+                            let registry = if self.registry.is_some() {
+                                explicit_registry.clone()
+                            } else {
+                                RegistrySource::from_package(&info)
+                            };
+                            let from_hash = if let RegistrySource::Git(_) = registry {
+                                self.registry_manager.get_pkg_hash(&info)
+                            } else {
+                                None
+                            };
+                            (name, (Some(info.version), from_hash, registry))
+                        })
                         .collect()
                 }
             };
 
-            let info = self
-                .registry_manager
-                .get_crate_info(&pkg.crate_name, pkg.to_version.as_ref());
-            let to_version = info.version;
-            let repository = if self.diff_rs { None } else { info.repository };
-
             if from_versions.is_empty() {
                 // explicit/non-dependency crate
-                from_versions.insert("".into(), None);
+                from_versions.insert("".into(), (None, None, self.explicit_registry()));
             }
 
-            for (target_name, from_version) in from_versions {
-                self.add_diff(
-                    &target_name,
-                    &pkg.crate_name,
-                    from_version,
-                    to_version.clone(),
-                    repository.clone(),
-                    &mut target_version_diffs,
-                );
+            requests.extend(from_versions.into_iter().map(
+                |(target_name, (from_version, from_hash, registry))| {
+                    (target_name, pkg, from_version, from_hash, registry)
+                },
+            ));
+        }
+
+        // target versions are downloaded anyway to get their repositories
+        let target_versions = self.target_versions(
+            requests
+                .iter()
+                .map(|(_, pkg, _, _, registry)| (*pkg, registry.clone()))
+                .collect(),
+        );
+        self.registry_manager.prefetch(
+            requests
+                .iter()
+                .zip(&target_versions)
+                .filter(|((_, _, _, _, registry), _)| !matches!(registry, RegistrySource::Git(_)))
+                .flat_map(|((_, pkg, from_version, _, registry), to_version)| {
+                    [from_version.as_ref(), to_version.as_ref()]
+                        .into_iter()
+                        .flatten()
+                        .map(|version| PrefetchCrate::new(&pkg.crate_name, version, registry))
+                }),
+        );
+
+        let mut target_version_diffs: IndexMap<String, Vec<CrateDiffInfo>> = IndexMap::new();
+        for (target_name, pkg, from_version, from_hash, registry) in requests {
+            if let RegistrySource::Git(source) = registry {
+                if pkg.to_version.is_some() {
+                    eprintln!(
+                        "[WARN] Target version of the git crate '{}' is ignored. The latest commit is used instead",
+                        pkg.crate_name
+                    );
+                }
+                if let Some(from_version) = from_version
+                    && let Some(diff) =
+                        self.build_git_diff(&pkg.crate_name, from_version, from_hash, source)
+                {
+                    target_version_diffs
+                        .entry(target_name)
+                        .or_default()
+                        .push(diff);
+                }
+                continue;
+            }
+
+            let info = self.registry_manager.get_crate_info(
+                &pkg.crate_name,
+                pkg.to_version.as_ref(),
+                &registry,
+            );
+
+            let from_info = self.version_info(&pkg.crate_name, from_version, &registry);
+            if let Some(diff) = self.build_diff(&pkg.crate_name, from_info, info, registry) {
+                target_version_diffs
+                    .entry(target_name)
+                    .or_default()
+                    .push(diff);
             }
         }
         target_version_diffs
@@ -135,134 +295,471 @@ impl CrateDiffBuilder {
     /// Called when crates are provided in the command line and they don't require current crate info:
     ///   ddd serde@1.0.223-1.0.226
     pub fn build_from_crates(
-        &mut self,
+        &self,
         crates: &[CrateDiffRequest],
     ) -> IndexMap<String, Vec<CrateDiffInfo>> {
-        let mut target_version_diffs = IndexMap::new();
-        for pkg in crates {
-            let info = self
-                .registry_manager
-                .get_crate_info(&pkg.crate_name, pkg.to_version.as_ref());
-            let to_version = info.version;
-            let repository = if self.diff_rs { None } else { info.repository };
-
-            self.add_diff(
-                "", // explicit/non-dependency crate
-                &pkg.crate_name,
-                pkg.from_version.clone(),
-                to_version,
-                repository,
-                &mut target_version_diffs,
+        let registry = self.explicit_registry();
+        let target_versions =
+            self.target_versions(crates.iter().map(|pkg| (pkg, registry.clone())).collect());
+        self.registry_manager
+            .prefetch(
+                crates
+                    .iter()
+                    .zip(&target_versions)
+                    .flat_map(|(pkg, to_version)| {
+                        [pkg.from_version.as_ref(), to_version.as_ref()]
+                            .into_iter()
+                            .flatten()
+                            .map(|version| PrefetchCrate::new(&pkg.crate_name, version, &registry))
+                    }),
             );
+
+        let diffs: Vec<_> = self
+            .worker_pool
+            .map(crates.iter().collect(), |pkg| {
+                let registry = registry.clone();
+                let info = self.registry_manager.get_crate_info(
+                    &pkg.crate_name,
+                    pkg.to_version.as_ref(),
+                    &registry,
+                );
+                let from_info =
+                    self.version_info(&pkg.crate_name, pkg.from_version.clone(), &registry);
+                self.build_diff(&pkg.crate_name, from_info, info, registry)
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut target_version_diffs = IndexMap::new();
+        if !diffs.is_empty() {
+            // explicit/non-dependency crates
+            target_version_diffs.insert("".to_string(), diffs);
         }
         target_version_diffs
     }
 
-    /// Retrieves nested dependencies of 2 versions of the same crate and returns 3 lists of changes:
-    /// - removed dependencies
-    /// - added dependencies
-    /// - updated dependencies
-    pub fn build_nested_deps(
-        &mut self,
-        diff: &CrateDiffInfo,
-    ) -> (Vec<CrateDiffInfo>, Vec<CrateDiffInfo>, Vec<CrateDiffInfo>) {
-        let from_nested_packages = self.get_nested_packages(&diff.name, diff.from_version.as_ref());
-        let mut to_nested_packages = self.get_nested_packages(&diff.name, diff.to_version.as_ref());
+    /// Latest versions of the registry crates from the registry index. Index entries are updated in batches
+    fn latest_versions(&self, crates: Vec<(String, RegistrySource)>) -> Vec<Option<Version>> {
+        self.registry_manager.refresh_index(crates.iter().cloned());
+        self.worker_pool.map(crates, |(crate_name, registry)| {
+            self.registry_manager.latest_version(&crate_name, &registry)
+        })
+    }
 
-        let mut removed_deps = vec![];
-        let mut updated_deps = vec![];
+    /// Requested target versions of the crates or the latest ones if they are not specified
+    fn target_versions(
+        &self,
+        requests: Vec<(&CrateDiffRequest, RegistrySource)>,
+    ) -> Vec<Option<Version>> {
+        let latest_versions = self.latest_versions(
+            requests
+                .iter()
+                .filter(|(pkg, _)| pkg.to_version.is_none())
+                .map(|(pkg, registry)| (pkg.crate_name.clone(), registry.clone()))
+                .collect(),
+        );
+        let mut latest_versions = latest_versions.into_iter();
+        requests
+            .into_iter()
+            .map(|(pkg, _)| match &pkg.to_version {
+                Some(version) => Some(version.clone()),
+                None => latest_versions.next().flatten(),
+            })
+            .collect()
+    }
+
+    /// Build diffs between the crates of the current and candidate vendor directories.
+    /// Called when the candidate vendor directory is provided in the command line:
+    ///   ddd --candidate-vendor-dir ../new/vendor
+    pub fn build_from_vendor_dirs(
+        &self,
+        current_dir: &VendorDir,
+        candidate_dir: &VendorDir,
+    ) -> IndexMap<String, Vec<CrateDiffInfo>> {
+        let mut changes = vec![];
+        let mut crate_names: Vec<_> = current_dir
+            .crates()
+            .iter()
+            .chain(candidate_dir.crates())
+            .map(|c| c.name.as_str())
+            .collect();
+        crate_names.sort();
+        crate_names.dedup();
+
+        for crate_name in crate_names {
+            // versions that are present only in one of the directories
+            let mut removed: Vec<_> = current_dir
+                .crates()
+                .iter()
+                .filter(|c| {
+                    c.name == crate_name && candidate_dir.find(crate_name, &c.version).is_none()
+                })
+                .collect();
+            let mut added: Vec<_> = candidate_dir
+                .crates()
+                .iter()
+                .filter(|c| {
+                    c.name == crate_name && current_dir.find(crate_name, &c.version).is_none()
+                })
+                .collect();
+
+            // pair the highest versions first, the remaining ones are added or removed
+            while !removed.is_empty() || !added.is_empty() {
+                let vendor_info = |vendor_crate: Option<&VendorCrate>| CrateInfo {
+                    version: vendor_crate.map(|c| c.version.clone()),
+                    repository: vendor_crate.and_then(|c| c.repository.clone()),
+                    homepage: vendor_crate.and_then(|c| c.homepage.clone()),
+                    repository_subpath: vendor_crate.and_then(|c| c.repository_subpath.clone()),
+                };
+
+                changes.push((
+                    crate_name,
+                    vendor_info(removed.pop()),
+                    vendor_info(added.pop()),
+                ));
+            }
+        }
+
+        // vendor directories don't record the crate source, so crates are looked up in the configured registry
+        let registry = self.explicit_registry();
+        let diffs: Vec<_> = self
+            .worker_pool
+            .map(changes, |(crate_name, from_info, to_info)| {
+                self.build_diff(crate_name, from_info, to_info, registry.clone())
+            })
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let mut target_version_diffs = IndexMap::new();
+        if !diffs.is_empty() {
+            target_version_diffs.insert("".to_string(), diffs);
+        }
+        target_version_diffs
+    }
+
+    /// Builds nested dependency diffs of all crate diffs when `show_all` is set.
+    /// Crate versions needed by the nested diffs are downloaded in batches before the diffs are built
+    pub fn build_dependency_diffs(
+        &self,
+        target_version_diffs: IndexMap<String, Vec<CrateDiffInfo>>,
+        show_all: bool,
+    ) -> IndexMap<String, Vec<DependencyDiff>> {
+        let diffs: Vec<_> = target_version_diffs
+            .into_iter()
+            .flat_map(|(target_name, diffs)| {
+                diffs
+                    .into_iter()
+                    .map(move |diff| (target_name.clone(), diff))
+            })
+            .collect();
+
+        let changes = if show_all {
+            // nested packages are resolved from the manifests of both crate versions
+            self.registry_manager.prefetch(
+                diffs
+                    .iter()
+                    .flat_map(|(_, diff)| diff_prefetch_crates(diff)),
+            );
+            self.worker_pool.map(diffs.iter().collect(), |(_, diff)| {
+                self.nested_changes(diff)
+            })
+        } else {
+            diffs.iter().map(|_| vec![]).collect()
+        };
+        if !self.diff_rs {
+            // nested packages are downloaded to get their hashes and repositories
+            self.registry_manager.prefetch(
+                changes
+                    .iter()
+                    .flatten()
+                    .flat_map(NestedChange::packages)
+                    .map(|pkg| {
+                        PrefetchCrate::new(
+                            &pkg.name,
+                            &pkg.version,
+                            &RegistrySource::from_package(pkg),
+                        )
+                    }),
+            );
+        }
+
+        let dep_diffs = self.worker_pool.map(
+            diffs.into_iter().zip(changes).collect(),
+            |((target_name, diff), changes)| {
+                let (mut removed_deps, mut added_deps, mut updated_deps) =
+                    self.build_nested_deps(changes);
+
+                removed_deps.sort_by(compare_diffs);
+                added_deps.sort_by(compare_diffs);
+                updated_deps.sort_by(compare_diffs);
+
+                let dep_diff = DependencyDiff {
+                    diff,
+                    removed_deps,
+                    added_deps,
+                    updated_deps,
+                };
+                (target_name, dep_diff)
+            },
+        );
+
+        let mut dependency_diffs: IndexMap<String, Vec<DependencyDiff>> = IndexMap::new();
+        for (target_name, dep_diff) in dep_diffs {
+            dependency_diffs
+                .entry(target_name)
+                .or_default()
+                .push(dep_diff);
+        }
+        dependency_diffs
+    }
+
+    /// Downloads both versions of all crates of the report in batches before their sources are analyzed
+    pub fn prefetch_report(&self, report: &DiffReport) {
+        self.registry_manager.prefetch(
+            report
+                .crate_diffs()
+                .into_iter()
+                .flat_map(diff_prefetch_crates),
+        );
+    }
 
+    /// Retrieves nested dependencies of 2 versions of the same crate and returns the changes between them
+    fn nested_changes(&self, diff: &CrateDiffInfo) -> Vec<NestedChange> {
+        let versions = vec![
+            (diff.from_version.as_ref(), diff.from_hash.as_deref()),
+            (diff.to_version.as_ref(), diff.to_hash.as_deref()),
+        ];
+        let mut nested_packages = self.worker_pool.map(versions, |(version, hash)| {
+            self.get_nested_packages(&diff.name, version, hash, &diff.registry)
+        });
+        let mut to_nested_packages = nested_packages.pop().unwrap_or_default();
+        let from_nested_packages = nested_packages.pop().unwrap_or_default();
+
+        let mut changes = vec![];
         for from_pkg in from_nested_packages {
             let pkg_idx = to_nested_packages
                 .iter()
-                .enumerate()
-                .find_map(|(i, to_pkg)| {
-                    if from_pkg.name == to_pkg.name {
-                        Some(i)
-                    } else {
-                        None
-                    }
-                });
-            let info = self
-                .registry_manager
-                .get_crate_info(&from_pkg.name, Some(&from_pkg.version));
-            let repository = if self.diff_rs { None } else { info.repository };
+                .position(|to_pkg| from_pkg.name == to_pkg.name);
 
             if let Some(index) = pkg_idx {
                 let to_pkg = to_nested_packages.remove(index);
 
-                if from_pkg.version != to_pkg.version {
-                    let (from_hash, to_hash) = if !self.diff_rs {
-                        let from_hash = self.registry_manager.get_pkg_hash(&from_pkg);
-                        let to_hash = self.registry_manager.get_pkg_hash(&to_pkg);
-                        (from_hash, to_hash)
-                    } else {
-                        (None, None)
-                    };
-
-                    updated_deps.push(CrateDiffInfo {
-                        name: from_pkg.name,
-                        from_version: Some(from_pkg.version),
-                        from_hash,
-                        to_version: Some(to_pkg.version),
-                        to_hash,
-                        repository,
-                    });
+                if from_pkg.version != to_pkg.version || from_pkg.commit != to_pkg.commit {
+                    changes.push(NestedChange::Updated(from_pkg, to_pkg));
                 }
             } else {
-                let from_hash = if !self.diff_rs {
-                    self.registry_manager.get_pkg_hash(&from_pkg)
+                changes.push(NestedChange::Removed(from_pkg));
+            }
+        }
+        // conver remaining to_nested_packages into the added changes
+        changes.extend(to_nested_packages.into_iter().map(NestedChange::Added));
+        changes
+    }
+
+    /// Builds diffs of the nested dependency changes and returns 3 lists:
+    /// - removed dependencies
+    /// - added dependencies
+    /// - updated dependencies
+    fn build_nested_deps(
+        &self,
+        changes: Vec<NestedChange>,
+    ) -> (Vec<CrateDiffInfo>, Vec<CrateDiffInfo>, Vec<CrateDiffInfo>) {
+        let mut removed_deps = vec![];
+        let mut added_deps = vec![];
+        let mut updated_deps = vec![];
+        for (change, diff) in self
+            .worker_pool
+            .map(changes, |change| self.build_nested_diff(change))
+        {
+            match change {
+                NestedChange::Removed(_) => removed_deps.push(diff),
+                NestedChange::Added(_) => added_deps.push(diff),
+                NestedChange::Updated(..) => updated_deps.push(diff),
+            }
+        }
+
+        (removed_deps, added_deps, updated_deps)
+    }
+
+    /// Diff of the nested dependency. Returns the change back to classify the diff
+    fn build_nested_diff(&self, change: NestedChange) -> (NestedChange, CrateDiffInfo) {
+        let diff = match &change {
+            NestedChange::Updated(from_pkg, to_pkg) => {
+                let registry = RegistrySource::from_package(from_pkg);
+                let (from_vcs, to_vcs) = if !self.diff_rs || from_pkg.commit.is_some() {
+                    let from_vcs = self.registry_manager.get_pkg_vcs_info(from_pkg);
+                    let to_vcs = self.registry_manager.get_pkg_vcs_info(to_pkg);
+                    (from_vcs, to_vcs)
+                } else {
+                    (None, None)
+                };
+                let from_info = self.nested_info(from_pkg);
+                let to_info = self.nested_info(to_pkg);
+
+                CrateDiffInfo {
+                    name: from_pkg.name.clone(),
+                    from_version: Some(from_pkg.version.clone()),
+                    from_hash: vcs_hash(&from_vcs),
+                    to_version: Some(to_pkg.version.clone()),
+                    to_hash: vcs_hash(&to_vcs),
+                    from_yanked: self.is_yanked(&from_pkg.name, Some(&from_pkg.version), &registry),
+                    to_yanked: self.is_yanked(&to_pkg.name, Some(&to_pkg.version), &registry),
+                    repository: to_info
+                        .repository
+                        .clone()
+                        .or_else(|| from_info.repository.clone()),
+                    from_repository: from_info.repository,
+                    to_repository: to_info.repository,
+                    from_homepage: from_info.homepage,
+                    to_homepage: to_info.homepage,
+                    path_in_vcs: path_in_vcs(
+                        &from_vcs,
+                        &to_vcs,
+                        &from_info.repository_subpath,
+                        &to_info.repository_subpath,
+                    ),
+                    registry,
+                }
+            }
+            NestedChange::Removed(from_pkg) => {
+                let registry = RegistrySource::from_package(from_pkg);
+                let from_vcs = if !self.diff_rs {
+                    self.registry_manager.get_pkg_vcs_info(from_pkg)
                 } else {
                     None
                 };
+                let from_info = self.nested_info(from_pkg);
 
-                removed_deps.push(CrateDiffInfo {
-                    name: from_pkg.name,
-                    from_version: Some(from_pkg.version),
-                    from_hash,
+                CrateDiffInfo {
+                    name: from_pkg.name.clone(),
+                    from_version: Some(from_pkg.version.clone()),
+                    from_hash: vcs_hash(&from_vcs),
                     to_version: None,
                     to_hash: None,
-                    repository,
-                });
+                    from_yanked: self.is_yanked(&from_pkg.name, Some(&from_pkg.version), &registry),
+                    to_yanked: false,
+                    repository: from_info.repository.clone(),
+                    from_repository: from_info.repository,
+                    to_repository: None,
+                    from_homepage: from_info.homepage,
+                    to_homepage: None,
+                    path_in_vcs: path_in_vcs(
+                        &from_vcs,
+                        &None,
+                        &from_info.repository_subpath,
+                        &None,
+                    ),
+                    registry,
+                }
             }
-        }
+            NestedChange::Added(to_pkg) => {
+                let registry = RegistrySource::from_package(to_pkg);
+                let to_vcs = if !self.diff_rs {
+                    self.registry_manager.get_pkg_vcs_info(to_pkg)
+                } else {
+                    None
+                };
+                let to_info = self.nested_info(to_pkg);
 
-        // conver remaining to_nested_packages into the added changes
-        let mut added_deps = vec![];
-        for dep in to_nested_packages {
-            let (to_hash, repository) = if !self.diff_rs {
-                let to_hash = self.registry_manager.get_pkg_hash(&dep);
-                let info = self
-                    .registry_manager
-                    .get_crate_info(&dep.name, Some(&dep.version));
-                (to_hash, info.repository)
-            } else {
-                (None, None)
-            };
+                CrateDiffInfo {
+                    name: to_pkg.name.clone(),
+                    from_version: None,
+                    from_hash: None,
+                    to_version: Some(to_pkg.version.clone()),
+                    to_hash: vcs_hash(&to_vcs),
+                    from_yanked: false,
+                    to_yanked: self.is_yanked(&to_pkg.name, Some(&to_pkg.version), &registry),
+                    repository: to_info.repository.clone(),
+                    from_repository: None,
+                    to_repository: to_info.repository,
+                    from_homepage: None,
+                    to_homepage: to_info.homepage,
+                    path_in_vcs: path_in_vcs(&None, &to_vcs, &None, &to_info.repository_subpath),
+                    registry,
+                }
+            }
+        };
+        (change, diff)
+    }
 
-            added_deps.push(CrateDiffInfo {
-                name: dep.name,
-                from_version: None,
-                from_hash: None,
-                to_version: Some(dep.version),
-                to_hash,
-                repository,
-            });
-        }
+    /// Info of the nested package version
+    fn nested_info(&self, pkg: &PackageIdInfo) -> CrateInfo {
+        let registry = RegistrySource::from_package(pkg);
+        self.version_info(&pkg.name, Some(pkg.version.clone()), &registry)
+    }
 
-        (removed_deps, added_deps, updated_deps)
+    /// Info of the specific crate version.
+    /// Repository is resolved for diff.rs links too, because repository changes are always checked
+    fn version_info(
+        &self,
+        crate_name: &str,
+        version: Option<Version>,
+        registry: &RegistrySource,
+    ) -> CrateInfo {
+        match version {
+            Some(version) => {
+                self.registry_manager
+                    .get_crate_info(crate_name, Some(&version), registry)
+            }
+            None => CrateInfo {
+                version: None,
+                repository: None,
+                homepage: None,
+                repository_subpath: None,
+            },
+        }
     }
 
     fn get_nested_packages(
         &self,
         crate_name: &str,
         version: Option<&Version>,
+        hash: Option<&str>,
+        registry: &RegistrySource,
     ) -> Vec<PackageIdInfo> {
         let Some(version) = version else {
             return vec![];
         };
-        let from_registry_path = self.registry_manager.get_crate_path(crate_name, version);
-        let cargo_meta = match CargoMeta::new(&from_registry_path) {
+        // nested packages are resolved to the latest compatible versions so they expire
+        let cache_key = self
+            .registry_manager
+            .crate_source(crate_name, version, registry)
+            .cache_key(&format!(
+                "{registry}|{crate_name}@{version}#{}",
+                hash.unwrap_or_default()
+            ));
+        let persistent_cache = self.registry_manager.persistent_cache();
+        if let Some(cache_key) = &cache_key
+            && let Some(packages) =
+                persistent_cache.get(CacheKind::NestedPackages, cache_key, Some(LATEST_TTL))
+        {
+            return packages;
+        }
+
+        let Some(crate_path) = self
+            .registry_manager
+            .fetch_crate(crate_name, version, hash, registry)
+        else {
+            return vec![];
+        };
+        // git crates are copied with the whole checkout to keep their workspace
+        let git_checkout = matches!(registry, RegistrySource::Git(_));
+        let source_root = if git_checkout {
+            crate_path
+                .ancestors()
+                .find(|path| path.join(UNPACKED_MARKER_FILE).exists())
+                .unwrap_or(&crate_path)
+        } else {
+            &crate_path
+        };
+        let cargo_meta = match CargoMeta::from_copy(
+            &crate_path,
+            source_root,
+            git_checkout,
+            self.registry_manager.offline(),
+        ) {
             Ok(cargo_meta) => cargo_meta,
             Err(err) => {
                 eprintln!(
@@ -271,45 +768,220 @@ impl CrateDiffBuilder {
                 return vec![];
             }
         };
-        cargo_meta.workspace_nested_packages()
+        let packages = cargo_meta.workspace_nested_packages();
+        if let Some(cache_key) = &cache_key {
+            persistent_cache.put(CacheKind::NestedPackages, cache_key, &packages);
+        }
+        packages
     }
 
-    fn add_diff(
+    fn build_diff(
         &self,
-        target_name: &str,
         crate_name: &str,
-        from_version: Option<Version>,
-        to_version: Option<Version>,
-        repository: Option<String>,
-        target_version_diffs: &mut IndexMap<String, Vec<CrateDiffInfo>>,
-    ) {
+        from_info: CrateInfo,
+        to_info: CrateInfo,
+        registry: RegistrySource,
+    ) -> Option<CrateDiffInfo> {
+        let from_version = from_info.version;
+        let to_version = to_info.version;
         if let Some(from_version) = &from_version
             && let Some(to_version) = &to_version
             && from_version == to_version
         {
-            return;
+            return None;
         }
 
-        let (from_hash, to_hash, repository) = if !self.diff_rs {
-            let from_hash = from_version
-                .as_ref()
-                .and_then(|version| self.registry_manager.get_crate_hash(crate_name, version));
-            let to_hash = to_version
-                .as_ref()
-                .and_then(|version| self.registry_manager.get_crate_hash(crate_name, version));
-            (from_hash, to_hash, repository)
+        let (from_vcs, to_vcs) = if !self.diff_rs {
+            let from_vcs = from_version.as_ref().and_then(|version| {
+                self.registry_manager
+                    .get_vcs_info(crate_name, version, &registry)
+            });
+            let to_vcs = to_version.as_ref().and_then(|version| {
+                self.registry_manager
+                    .get_vcs_info(crate_name, version, &registry)
+            });
+            (from_vcs, to_vcs)
         } else {
-            (None, None, None)
+            (None, None)
         };
-        let deps = target_version_diffs.entry(target_name.into()).or_default();
 
-        deps.push(CrateDiffInfo {
+        let from_yanked = self.is_yanked(crate_name, from_version.as_ref(), &registry);
+        let to_yanked = self.is_yanked(crate_name, to_version.as_ref(), &registry);
+        Some(CrateDiffInfo {
             name: crate_name.into(),
             from_version,
-            from_hash,
+            from_hash: vcs_hash(&from_vcs),
             to_version,
-            to_hash,
-            repository, // TODO: can a repository of the same crate change between versions?
-        });
+            to_hash: vcs_hash(&to_vcs),
+            from_yanked,
+            to_yanked,
+            repository: to_info
+                .repository
+                .clone()
+                .or_else(|| from_info.repository.clone()),
+            from_repository: from_info.repository,
+            to_repository: to_info.repository,
+            from_homepage: from_info.homepage,
+            to_homepage: to_info.homepage,
+            path_in_vcs: path_in_vcs(
+                &from_vcs,
+                &to_vcs,
+                &from_info.repository_subpath,
+                &to_info.repository_subpath,
+            ),
+            registry,
+        })
+    }
+
+    /// Diff between the locked commit of the git crate and the newest commit of its tracked reference
+    fn build_git_diff(
+        &self,
+        crate_name: &str,
+        from_version: Version,
+        from_hash: Option<String>,
+        source: GitSource,
+    ) -> Option<CrateDiffInfo> {
+        let from_hash = from_hash?;
+        let (to_version, to_hash) = self.registry_manager.get_git_latest(crate_name, &source)?;
+        if from_hash == to_hash {
+            return None;
+        }
+
+        Some(CrateDiffInfo {
+            name: crate_name.into(),
+            from_version: Some(from_version),
+            from_hash: Some(from_hash),
+            to_version: Some(to_version),
+            to_hash: Some(to_hash),
+            from_yanked: false,
+            to_yanked: false,
+            repository: Some(source.repository()),
+            from_repository: Some(source.repository()),
+            to_repository: Some(source.repository()),
+            from_homepage: None,
+            to_homepage: None,
+            path_in_vcs: None,
+            registry: RegistrySource::Git(source),
+        })
+    }
+
+    /// Checks the registry index whether the crate version is yanked
+    fn is_yanked(
+        &self,
+        crate_name: &str,
+        version: Option<&Version>,
+        registry: &RegistrySource,
+    ) -> bool {
+        version.is_some_and(|version| {
+            self.registry_manager
+                .is_yanked(crate_name, version, registry)
+        })
+    }
+
+    /// Registry of the explicitly specified crates. Defaults to crates.io
+    fn explicit_registry(&self) -> RegistrySource {
+        RegistrySource::from_name(self.registry.as_deref())
+    }
+}
+
+/// Registry crate versions of the diff
+fn diff_prefetch_crates(diff: &CrateDiffInfo) -> impl Iterator<Item = PrefetchCrate> + '_ {
+    [diff.from_version.as_ref(), diff.to_version.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|version| PrefetchCrate::new(&diff.name, version, &diff.registry))
+}
+
+/// Current version is the latest one or newer, e.g. a pre-release of the next version while only stable versions are picked
+fn up_to_date(version: &Version, latest_version: Option<&Version>) -> bool {
+    latest_version.is_some_and(|latest_version| version >= latest_version)
+}
+
+fn vcs_hash(vcs_info: &Option<VcsInfo>) -> Option<String> {
+    vcs_info.as_ref().map(|vcs_info| vcs_info.hash.clone())
+}
+
+/// Crate folder inside the repository. The target version has the priority.
+/// Folder from the `tree`/`blob` repository link is used if the VCS info is not available
+fn path_in_vcs(
+    from_vcs: &Option<VcsInfo>,
+    to_vcs: &Option<VcsInfo>,
+    from_subpath: &Option<String>,
+    to_subpath: &Option<String>,
+) -> Option<String> {
+    match to_vcs.as_ref().or(from_vcs.as_ref()) {
+        Some(vcs_info) => vcs_info.path_in_vcs.clone(),
+        None => to_subpath.clone().or_else(|| from_subpath.clone()),
+    }
+}
+
+fn compare_diffs(a: &CrateDiffInfo, b: &CrateDiffInfo) -> Ordering {
+    a.name
+        .cmp(&b.name)
+        .then_with(|| a.from_version.cmp(&b.from_version))
+        .then_with(|| a.to_version.cmp(&b.to_version))
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::crate_diff_builder::{path_in_vcs, up_to_date};
+    use crate::vcs_info::VcsInfo;
+
+    #[test]
+    fn test_up_to_date() {
+        let version = |version: &str| Version::parse(version).unwrap();
+
+        assert!(up_to_date(&version("1.9.0"), Some(&version("1.9.0"))));
+        assert!(!up_to_date(&version("1.8.0"), Some(&version("1.9.0"))));
+        // pre-release of the next version is never downgraded to the latest stable one
+        assert!(up_to_date(&version("2.0.0-rc.1"), Some(&version("1.9.0"))));
+        assert!(!up_to_date(&version("2.0.0-rc.1"), Some(&version("2.0.0"))));
+        assert!(!up_to_date(&version("1.9.0"), None));
+    }
+
+    #[test]
+    fn test_path_in_vcs() {
+        let subpath = |path: Option<&str>| path.map(String::from);
+        let vcs = |path: Option<&str>| {
+            Some(VcsInfo {
+                hash: "abc".into(),
+                path_in_vcs: path.map(Into::into),
+            })
+        };
+
+        assert_eq!(
+            Some("crates/bar".to_string()),
+            path_in_vcs(
+                &vcs(Some("crates/foo")),
+                &vcs(Some("crates/bar")),
+                &subpath(None),
+                &subpath(Some("crates/baz"))
+            )
+        );
+        // crate in the repository root
+        assert_eq!(
+            None,
+            path_in_vcs(
+                &None,
+                &vcs(None),
+                &subpath(None),
+                &subpath(Some("crates/baz"))
+            )
+        );
+        assert_eq!(
+            Some("crates/baz".to_string()),
+            path_in_vcs(
+                &None,
+                &None,
+                &subpath(Some("crates/foo")),
+                &subpath(Some("crates/baz"))
+            )
+        );
+        assert_eq!(
+            Some("crates/foo".to_string()),
+            path_in_vcs(&None, &None, &subpath(Some("crates/foo")), &subpath(None))
+        );
     }
 }
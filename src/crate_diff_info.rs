@@ -1,5 +1,7 @@
 use semver::Version;
 
+use crate::registry_source::RegistrySource;
+
 /// The crate diff information
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CrateDiffInfo {
@@ -13,6 +15,35 @@ pub struct CrateDiffInfo {
     pub to_version: Option<Version>,
     /// Target crate repository commit hash
     pub to_hash: Option<String>,
-    /// Crate repository path
+    /// Initial crate version is yanked from the registry
+    pub from_yanked: bool,
+    /// Target crate version is yanked from the registry
+    pub to_yanked: bool,
+    /// Crate repository path of the target version. Removed crates have the repository of the initial version
     pub repository: Option<String>,
+    /// Crate repository path of the initial version
+    pub from_repository: Option<String>,
+    /// Crate repository path of the target version. It's missing if the target version doesn't specify it
+    pub to_repository: Option<String>,
+    /// Crate homepage of the initial version
+    pub from_homepage: Option<String>,
+    /// Crate homepage of the target version
+    pub to_homepage: Option<String>,
+    /// Crate folder inside the repository
+    pub path_in_vcs: Option<String>,
+    /// Registry the crate is published to
+    pub registry: RegistrySource,
+}
+
+impl CrateDiffInfo {
+    /// Yanked versions of the diff: initial first
+    pub fn yanked_versions(&self) -> Vec<&Version> {
+        [
+            (&self.from_version, self.from_yanked),
+            (&self.to_version, self.to_yanked),
+        ]
+        .into_iter()
+        .filter_map(|(version, yanked)| version.as_ref().filter(|_| yanked))
+        .collect()
+    }
 }
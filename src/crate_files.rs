@@ -0,0 +1,193 @@
+use std::path::{Component, Path};
+
+use anyhow::{Context, Result};
+
+/// Files created by cargo while unpacking or vendoring the crate. They are not a part of the package.
+const CARGO_MARKER_FILES: [&str; 2] = [".cargo-ok", ".cargo-checksum.json"];
+
+/// Repository folder of the git checkout. It's not a part of the package.
+const GIT_DIR: &str = ".git";
+
+/// Returns sorted relative paths (with `/` separators) of all files in the crate source folder.
+/// Symbolic links are returned as files and are not followed.
+/// The repository folder is skipped only in the root of the `git_checkout`, published packages may contain it.
+pub fn list_crate_files(crate_path: &Path, git_checkout: bool) -> Result<Vec<String>> {
+    let mut files = vec![];
+    collect_files(crate_path, "", git_checkout, &mut files)?;
+    files.retain(|f| !CARGO_MARKER_FILES.contains(&f.as_str()));
+    files.sort();
+    Ok(files)
+}
+
+/// Copies all files of the crate source folder into the destination folder.
+/// Symbolic links are recreated if they point inside of the crate folder. Other links are skipped and never followed.
+pub fn copy_crate_files(crate_path: &Path, dest_path: &Path, git_checkout: bool) -> Result<()> {
+    for file in list_crate_files(crate_path, git_checkout)? {
+        let src_file = crate_path.join(&file);
+        let dest_file = dest_path.join(&file);
+        if let Some(parent) = dest_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if std::fs::symlink_metadata(&src_file)?.is_symlink() {
+            copy_symlink(crate_path, &file, &dest_file)?;
+            continue;
+        }
+        std::fs::copy(&src_file, &dest_file)
+            .with_context(|| format!("Cannot copy file {src_file:?} to {dest_file:?}"))?;
+    }
+    Ok(())
+}
+
+/// Recreates the symbolic link `file` of the crate folder if its target is inside of the crate folder
+fn copy_symlink(crate_path: &Path, file: &str, dest_file: &Path) -> Result<()> {
+    let src_file = crate_path.join(file);
+    let target = std::fs::read_link(&src_file)?;
+    if !symlink_inside(file, &target) {
+        eprintln!(
+            "[WARN] Symbolic link {src_file:?} points outside of the crate folder. It's skipped"
+        );
+        return Ok(());
+    }
+    create_symlink(&target, dest_file)
+        .with_context(|| format!("Cannot create symbolic link {dest_file:?} to {target:?}"))
+}
+
+/// Relative target of the symbolic link `file` never leaves the crate folder
+fn symlink_inside(file: &str, target: &Path) -> bool {
+    // number of folders between the link and the crate folder
+    let mut depth = file.split('/').count() - 1;
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent_depth) => depth = parent_depth,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    // symbolic links are skipped on the other platforms
+    Ok(())
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    git_checkout: bool,
+    files: &mut Vec<String>,
+) -> Result<()> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Cannot read folder: {dir:?}"))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let rel_path = format!("{prefix}{file_name}");
+
+        if entry.file_type()?.is_dir() {
+            if git_checkout && prefix.is_empty() && file_name == GIT_DIR {
+                continue;
+            }
+            collect_files(&entry.path(), &format!("{rel_path}/"), false, files)?;
+        } else {
+            files.push(rel_path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::crate_files::{copy_crate_files, list_crate_files, symlink_inside};
+
+    #[test]
+    fn test_copy_crate_files() {
+        let crate_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(crate_dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(crate_dir.path().join(".git")).unwrap();
+        std::fs::write(crate_dir.path().join("Cargo.toml"), "[package]").unwrap();
+        std::fs::write(crate_dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(crate_dir.path().join(".git/HEAD"), "").unwrap();
+        std::fs::write(crate_dir.path().join(".cargo-ok"), "").unwrap();
+
+        let dest_dir = tempfile::tempdir().unwrap();
+        copy_crate_files(crate_dir.path(), dest_dir.path(), true).unwrap();
+
+        assert_eq!(
+            vec!["Cargo.toml".to_string(), "src/lib.rs".to_string()],
+            list_crate_files(dest_dir.path(), false).unwrap()
+        );
+        assert!(!dest_dir.path().join(".git").exists());
+        assert!(!dest_dir.path().join(".cargo-ok").exists());
+
+        // published packages may contain the repository folder
+        assert_eq!(
+            vec![
+                ".git/HEAD".to_string(),
+                "Cargo.toml".to_string(),
+                "src/lib.rs".to_string()
+            ],
+            list_crate_files(crate_dir.path(), false).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_symlink_inside() {
+        assert!(symlink_inside("src/lib.rs", Path::new("../README.md")));
+        assert!(symlink_inside(
+            "src/a/lib.rs",
+            Path::new("./../b/../lib.rs")
+        ));
+        assert!(symlink_inside("link", Path::new("src")));
+        assert!(!symlink_inside("src/lib.rs", Path::new("../../secret")));
+        assert!(!symlink_inside("link", Path::new("src/../../secret")));
+        assert!(!symlink_inside("link", Path::new("/etc/passwd")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_copy_symlinks() {
+        let outside_dir = tempfile::tempdir().unwrap();
+        std::fs::write(outside_dir.path().join("secret"), "").unwrap();
+        let crate_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(crate_dir.path().join("src")).unwrap();
+        std::fs::write(crate_dir.path().join("src/lib.rs"), "").unwrap();
+        let symlink = |target: &Path, link: &str| {
+            std::os::unix::fs::symlink(target, crate_dir.path().join(link)).unwrap()
+        };
+        symlink(Path::new("src"), "src-dir");
+        symlink(Path::new("src/lib.rs"), "lib.rs");
+        symlink(&outside_dir.path().join("secret"), "absolute");
+        symlink(Path::new("../secret"), "relative");
+
+        let dest_dir = tempfile::tempdir().unwrap();
+        copy_crate_files(crate_dir.path(), dest_dir.path(), false).unwrap();
+
+        assert_eq!(
+            vec![
+                "lib.rs".to_string(),
+                "src-dir".to_string(),
+                "src/lib.rs".to_string()
+            ],
+            list_crate_files(dest_dir.path(), false).unwrap()
+        );
+        assert!(dest_dir.path().join("src-dir/lib.rs").exists());
+        assert_eq!(
+            Path::new("src"),
+            std::fs::read_link(dest_dir.path().join("src-dir")).unwrap()
+        );
+    }
+}
@@ -1,7 +1,13 @@
 use semver::Version;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrateInfo {
     pub version: Option<Version>,
     pub repository: Option<String>,
+    #[serde(default)]
+    pub homepage: Option<String>,
+    /// Crate folder from the `tree`/`blob` repository link
+    #[serde(default)]
+    pub repository_subpath: Option<String>,
 }
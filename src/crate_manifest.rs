@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Subset of the crate manifest (Cargo.toml) fields
+#[derive(Debug, Default, Deserialize)]
+pub struct CrateManifest {
+    #[serde(default)]
+    pub package: ManifestPackage,
+    /// Library target
+    pub lib: Option<ManifestTarget>,
+}
+
+/// Fields of the `[package]` manifest section
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestPackage {
+    /// Crate name
+    pub name: Option<String>,
+    /// Crate version
+    pub version: Option<String>,
+    /// Repository URL
+    pub repository: Option<String>,
+    /// Homepage URL
+    pub homepage: Option<String>,
+    /// SPDX license expression
+    pub license: Option<String>,
+    /// Path to the non-standard license file
+    pub license_file: Option<String>,
+}
+
+/// Fields of the target manifest sections like `[lib]`
+#[derive(Debug, Default, Deserialize)]
+pub struct ManifestTarget {
+    /// Target name
+    pub name: Option<String>,
+    /// Path to the target root source file
+    pub path: Option<String>,
+}
+
+impl CrateManifest {
+    /// Reads manifest of the crate located in the `crate_path` folder
+    pub fn read(crate_path: &Path) -> Result<Self> {
+        let manifest_path = crate_path.join("Cargo.toml");
+        let manifest_data = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Cannot read manifest: {manifest_path:?}"))?;
+        toml::from_str(&manifest_data)
+            .with_context(|| format!("Cannot parse manifest: {manifest_path:?}"))
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+
+use anyhow::Result;
+use semver::Version;
+
+use crate::cargo_runner::CargoRunner;
+use crate::registry_source::RegistrySource;
+
+/// Registry crate version that is downloaded in a batch
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PrefetchCrate {
+    pub name: String,
+    pub version: Version,
+    pub registry: RegistrySource,
+    /// Lockfile entry of the yanked crate
+    pub locked: Option<LockedPackage>,
+}
+
+impl PrefetchCrate {
+    pub fn new(name: &str, version: &Version, registry: &RegistrySource) -> Self {
+        Self {
+            name: name.into(),
+            version: version.clone(),
+            registry: registry.clone(),
+            locked: None,
+        }
+    }
+}
+
+/// `Cargo.lock` entry of the crate. Cargo resolves the yanked versions only if they are locked
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LockedPackage {
+    /// Package source: `registry+<index URL>` or `sparse+<index URL>`
+    pub source: String,
+    /// SHA-256 checksum of the `.crate` archive from the registry index
+    pub checksum: String,
+}
+
+/// Splits crates into layers so that every layer contains at most one version of the semver compatible range of the crate.
+/// Cargo cannot resolve 2 compatible versions of the same crate in one manifest.
+pub fn semver_layers(crates: BTreeSet<PrefetchCrate>) -> Vec<Vec<PrefetchCrate>> {
+    let mut layers: Vec<(BTreeSet<_>, Vec<_>)> = vec![];
+    for krate in crates {
+        let key = (
+            krate.registry.clone(),
+            krate.name.clone(),
+            compatible_range(&krate.version),
+        );
+        let index = match layers.iter().position(|(keys, _)| !keys.contains(&key)) {
+            Some(index) => index,
+            None => {
+                layers.push(Default::default());
+                layers.len() - 1
+            }
+        };
+        layers[index].0.insert(key);
+        layers[index].1.push(krate);
+    }
+    layers.into_iter().map(|(_, crates)| crates).collect()
+}
+
+/// Downloads all crates of the layer into the local registry with a single 'cargo fetch' run
+/// over the synthesized manifest that depends on the exact crate versions.
+/// Crates with the lockfile entries are locked in the synthesized `Cargo.lock`
+pub fn fetch_layer(layer: &[PrefetchCrate]) -> Result<()> {
+    run_manifest(
+        "fetch",
+        layer.iter().map(|krate| {
+            (
+                krate.name.as_str(),
+                format!("={}", krate.version),
+                &krate.registry,
+            )
+        }),
+        lockfile(layer),
+    )
+}
+
+/// `Cargo.lock` with the locked crates of the layer. Other dependencies are resolved by cargo
+fn lockfile(layer: &[PrefetchCrate]) -> Option<String> {
+    let locked: Vec<_> = layer
+        .iter()
+        .filter_map(|krate| {
+            let locked = krate.locked.as_ref()?;
+            Some(format!(
+                "[[package]]\nname = \"{}\"\nversion = \"{}\"\nsource = \"{}\"\nchecksum = \"{}\"\n",
+                krate.name, krate.version, locked.source, locked.checksum
+            ))
+        })
+        .collect();
+    (!locked.is_empty()).then(|| format!("version = 3\n\n{}", locked.join("\n")))
+}
+
+/// Updates the index cache entries of the crates with a single 'cargo generate-lockfile' run
+/// over the synthesized manifest that depends on any version of every crate
+pub fn update_index<'a>(
+    crates: impl IntoIterator<Item = &'a (String, RegistrySource)>,
+) -> Result<()> {
+    run_manifest(
+        "generate-lockfile",
+        crates
+            .into_iter()
+            .map(|(name, registry)| (name.as_str(), "*".to_string(), registry)),
+        None,
+    )
+}
+
+/// Runs the cargo subcommand over the temporary manifest with the dependencies: `(name, version requirement, registry)`
+/// and the optional lockfile
+fn run_manifest<'a>(
+    subcommand: &str,
+    deps: impl Iterator<Item = (&'a str, String, &'a RegistrySource)>,
+    lockfile: Option<String>,
+) -> Result<()> {
+    let manifest_dir = tempfile::Builder::new()
+        .prefix("cargo-ddd-prefetch-")
+        .tempdir()?;
+
+    let mut manifest = String::from(
+        "[package]\nname = \"cargo-ddd-prefetch\"\nversion = \"0.0.0\"\nedition = \"2021\"\npublish = false\n\n\
+         [lib]\npath = \"lib.rs\"\n\n[workspace]\n\n[dependencies]\n",
+    );
+    // registries that are known only by the index URL get the temporary names
+    let mut index_registries = BTreeMap::new();
+    for (i, (name, version_req, registry)) in deps.enumerate() {
+        let registry = match registry {
+            RegistrySource::CratesIo | RegistrySource::Git(_) => String::new(),
+            RegistrySource::Named(name) => format!(", registry = \"{name}\""),
+            RegistrySource::Index(index) => {
+                let count = index_registries.len();
+                let name = index_registries
+                    .entry(index.clone())
+                    .or_insert_with(|| format!("cargo-ddd-index-{count}"));
+                format!(", registry = \"{name}\"")
+            }
+        };
+        manifest += &format!(
+            "dep-{i} = {{ package = \"{name}\", version = \"{version_req}\"{registry} }}\n"
+        );
+    }
+    let manifest_path = manifest_dir.path().join("Cargo.toml");
+    std::fs::write(&manifest_path, manifest)?;
+    std::fs::write(manifest_dir.path().join("lib.rs"), "")?;
+    if let Some(lockfile) = lockfile {
+        std::fs::write(manifest_dir.path().join("Cargo.lock"), lockfile)?;
+    }
+
+    let mut cargo_runner = CargoRunner::new(None);
+    for (index, name) in index_registries {
+        cargo_runner = cargo_runner.env(
+            format!(
+                "CARGO_REGISTRIES_{}_INDEX",
+                name.to_uppercase().replace('-', "_")
+            ),
+            index,
+        );
+    }
+    cargo_runner.run(
+        subcommand,
+        [OsStr::new("--manifest-path"), manifest_path.as_os_str()],
+    )?;
+    Ok(())
+}
+
+/// Versions of the same range are semver compatible: `1.2.3` -> `1`, `0.2.3` -> `0.2`, `0.0.3` -> `0.0.3`
+fn compatible_range(version: &Version) -> String {
+    match (version.major, version.minor) {
+        (0, 0) => format!("0.0.{}", version.patch),
+        (0, minor) => format!("0.{minor}"),
+        (major, _) => major.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::crate_prefetch::{LockedPackage, PrefetchCrate, lockfile, semver_layers};
+    use crate::registry_source::RegistrySource;
+
+    #[test]
+    fn test_lockfile() {
+        let registry = RegistrySource::CratesIo;
+        let mut yanked = PrefetchCrate::new("foo", &Version::new(1, 0, 0), &registry);
+        yanked.locked = Some(LockedPackage {
+            source: "registry+https://github.com/rust-lang/crates.io-index".into(),
+            checksum: "abc".into(),
+        });
+        let krate = PrefetchCrate::new("bar", &Version::new(2, 0, 0), &registry);
+
+        assert_eq!(None, lockfile(std::slice::from_ref(&krate)));
+        assert_eq!(
+            Some(
+                "version = 3\n\n[[package]]\nname = \"foo\"\nversion = \"1.0.0\"\n\
+                 source = \"registry+https://github.com/rust-lang/crates.io-index\"\nchecksum = \"abc\"\n"
+                    .to_string()
+            ),
+            lockfile(&[yanked, krate])
+        );
+    }
+
+    #[test]
+    fn test_semver_layers() {
+        let krate = |name: &str, version: &str| {
+            PrefetchCrate::new(
+                name,
+                &Version::parse(version).unwrap(),
+                &RegistrySource::CratesIo,
+            )
+        };
+        let layers = semver_layers(
+            [
+                krate("foo", "1.0.0"),
+                krate("foo", "1.2.0"),
+                krate("foo", "2.0.0"),
+                krate("bar", "0.1.0"),
+                krate("bar", "0.2.0"),
+                krate("baz", "0.0.1"),
+                krate("baz", "0.0.2"),
+                krate("qux", "1.0.0"),
+                krate("qux", "1.0.1"),
+                krate("qux", "1.1.0"),
+            ]
+            .into_iter()
+            .collect(),
+        );
+
+        let layers: Vec<Vec<_>> = layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|c| format!("{}@{}", c.name, c.version))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                vec![
+                    "bar@0.1.0",
+                    "bar@0.2.0",
+                    "baz@0.0.1",
+                    "baz@0.0.2",
+                    "foo@1.0.0",
+                    "foo@2.0.0",
+                    "qux@1.0.0"
+                ],
+                vec!["foo@1.2.0", "qux@1.0.1"],
+                vec!["qux@1.1.0"],
+            ],
+            layers
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use ureq::Agent;
+
+use crate::cargo_cache_backend::registry_dir_name;
+use crate::crate_archive::{unpack_crate, unpacked_crate};
+use crate::registry_backend::{CrateMetadata, RegistryBackend, VersionInfo};
+
+/// Base URL of the crates.io HTTP API
+pub const CRATES_IO_API_URL: &str = "https://crates.io";
+
+/// crates.io requires the user agent to identify the client
+const USER_AGENT: &str = concat!(
+    "cargo-ddd/",
+    env!("CARGO_PKG_VERSION"),
+    " (https://github.com/HaronK/cargo-ddd)"
+);
+
+/// Response of the `/api/v1/crates/{name}` endpoint
+#[derive(Debug, Deserialize)]
+struct ApiCrateResponse {
+    #[serde(rename = "crate")]
+    krate: ApiCrate,
+    versions: Vec<ApiVersion>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiCrate {
+    repository: Option<String>,
+    homepage: Option<String>,
+}
+
+/// Response of the `/api/v1/crates/{name}/{version}` endpoint
+#[derive(Debug, Deserialize)]
+struct ApiVersionResponse {
+    version: ApiVersion,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiVersion {
+    num: Version,
+    #[serde(default)]
+    yanked: bool,
+    /// SHA-256 checksum of the `.crate` archive
+    checksum: Option<String>,
+    repository: Option<String>,
+    homepage: Option<String>,
+}
+
+/// Registry backend on top of the crates.io compatible HTTP API.
+/// Crate sources are unpacked into the temporary folder.
+#[derive(Clone)]
+pub struct CratesIoBackend {
+    /// API base URL without the trailing slash
+    base_url: String,
+    agent: Agent,
+    /// Folder the downloaded crates are unpacked to
+    download_path: PathBuf,
+}
+
+impl CratesIoBackend {
+    pub fn new(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let agent = Agent::config_builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .into();
+        let download_path = std::env::temp_dir()
+            .join("cargo-ddd")
+            .join(registry_dir_name(&base_url));
+
+        Self {
+            base_url,
+            agent,
+            download_path,
+        }
+    }
+
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let url = format!("{}{path}", self.base_url);
+        let body = self
+            .agent
+            .get(&url)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .with_context(|| format!("Request failed: {url}"))?;
+        serde_json::from_str(&body).with_context(|| format!("Cannot parse response of {url}"))
+    }
+}
+
+impl RegistryBackend for CratesIoBackend {
+    fn list_versions(&self, crate_name: &str) -> Result<Vec<VersionInfo>> {
+        let response: ApiCrateResponse = self.get_json(&format!("/api/v1/crates/{crate_name}"))?;
+        let mut versions: Vec<_> = response
+            .versions
+            .into_iter()
+            .map(|v| VersionInfo {
+                version: v.num,
+                yanked: v.yanked,
+                checksum: v.checksum,
+            })
+            .collect();
+        versions.sort_by(|a, b| a.version.cmp(&b.version));
+        Ok(versions)
+    }
+
+    /// Version metadata. Falls back to the crate metadata if the registry doesn't store it per version
+    fn crate_metadata(&self, crate_name: &str, version: &Version) -> Result<CrateMetadata> {
+        let response: ApiVersionResponse =
+            self.get_json(&format!("/api/v1/crates/{crate_name}/{version}"))?;
+        if response.version.repository.is_some() || response.version.homepage.is_some() {
+            return Ok(CrateMetadata {
+                repository: response.version.repository,
+                homepage: response.version.homepage,
+            });
+        }
+
+        let response: ApiCrateResponse = self.get_json(&format!("/api/v1/crates/{crate_name}"))?;
+        Ok(CrateMetadata {
+            repository: response.krate.repository,
+            homepage: response.krate.homepage,
+        })
+    }
+
+    fn source_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf> {
+        unpacked_crate(&self.download_path, &format!("{crate_name}-{version}"))
+    }
+
+    fn download_source(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        let archive_path = self.download_archive(crate_name, version)?;
+        let archive = std::fs::File::open(&archive_path)?;
+        unpack_crate(
+            archive,
+            &self.download_path,
+            &format!("{crate_name}-{version}"),
+        )
+        .with_context(|| format!("Cannot unpack '{crate_name}@{version}' crate"))
+    }
+
+    /// Downloads the archive into the temporary folder
+    fn download_archive(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        let archive_path = self
+            .download_path
+            .join(format!("{crate_name}-{version}.crate"));
+        if archive_path.exists() {
+            return Ok(archive_path);
+        }
+
+        let url = format!(
+            "{}/api/v1/crates/{crate_name}/{version}/download",
+            self.base_url
+        );
+        let response = self
+            .agent
+            .get(&url)
+            .call()
+            .with_context(|| format!("Request failed: {url}"))?;
+
+        std::fs::create_dir_all(&self.download_path)?;
+        // download into the temporary file first so the interrupted download is never used
+        let mut archive = tempfile::NamedTempFile::new_in(&self.download_path)?;
+        std::io::copy(&mut response.into_body().into_reader(), &mut archive)
+            .with_context(|| format!("Cannot download '{crate_name}@{version}' crate"))?;
+        archive.persist(&archive_path)?;
+        Ok(archive_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use semver::Version;
+
+    use crate::crates_io_backend::CratesIoBackend;
+    use crate::registry_backend::{RegistryBackend, latest_version};
+
+    /// Serves the responses by the request path. Other paths get 404
+    fn start_mock_server(responses: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                // skip headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = match responses.iter().find(|(p, _)| *p == path) {
+                    Some((_, body)) => ("200 OK", body.as_slice()),
+                    None => ("404 Not Found", &b"{}"[..]),
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    fn crate_archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(vec![], Compression::default()));
+        for (path, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, data.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_crates_io_backend() {
+        let base_url = start_mock_server(vec![
+            (
+                "/api/v1/crates/foo",
+                br#"{"crate":{"name":"foo","repository":"https://github.com/foo/foo/","homepage":null},
+                "versions":[{"num":"1.1.0","yanked":true,"checksum":"bb"},{"num":"1.0.0","yanked":false,"checksum":"aa"}]}"#
+                    .to_vec(),
+            ),
+            (
+                "/api/v1/crates/foo/1.0.0",
+                br#"{"version":{"num":"1.0.0","yanked":false}}"#.to_vec(),
+            ),
+            (
+                "/api/v1/crates/foo/1.0.0/download",
+                crate_archive(&[("foo-1.0.0/Cargo.toml", "[package]\nname = \"foo\"\n")]),
+            ),
+            (
+                "/api/v1/crates/foo/1.1.0/download",
+                crate_archive(&[("foo-1.0.0/Cargo.toml", "")]),
+            ),
+        ]);
+        let backend = CratesIoBackend::new(&format!("{base_url}/"));
+        let version = Version::new(1, 0, 0);
+
+        let versions = backend.list_versions("foo").unwrap();
+        assert_eq!(2, versions.len());
+        assert_eq!(Some(version.clone()), latest_version(&versions, false));
+        assert_eq!(Some("aa"), versions[0].checksum.as_deref());
+        assert!(backend.list_versions("bar").is_err());
+
+        assert_eq!(
+            Some("https://github.com/foo/foo".to_string()),
+            backend
+                .crate_metadata("foo", &version)
+                .unwrap()
+                .repository_url()
+        );
+
+        assert!(backend.source_path("foo", &version).is_none());
+        let crate_path = backend.download_source("foo", &version);
+        let unpacked = backend.source_path("foo", &version);
+        let archive_path = backend.download_archive("foo", &version);
+        let mismatched = backend.download_source("foo", &Version::new(1, 1, 0));
+        std::fs::remove_dir_all(&backend.download_path).unwrap();
+
+        let crate_path = crate_path.unwrap();
+        assert!(crate_path.ends_with("foo-1.0.0"));
+        assert_eq!(Some(crate_path), unpacked);
+        assert!(archive_path.unwrap().ends_with("foo-1.0.0.crate"));
+        assert!(mismatched.is_err());
+    }
+}
@@ -1,9 +1,63 @@
+use std::collections::BTreeSet;
+
 use indexmap::IndexMap;
 
+use crate::api_comparator::ApiDiff;
+use crate::crate_archive::ChecksumMismatch;
+use crate::crate_diff_info::CrateDiffInfo;
 use crate::dependency_diff::DependencyDiff;
+use crate::license_checker::LicenseDiff;
+use crate::package_inspector::PackageAnomalies;
+use crate::repository_checker::RepositoryChange;
+use crate::size_comparator::SizeReport;
+use crate::source_comparator::SourceDiff;
 
 /// Diff report for all requested crates
 pub struct DiffReport {
     /// Dependency diffs per workspace target
     pub dependency_diffs: IndexMap<String, Vec<DependencyDiff>>,
+    /// `.crate` archives whose checksums don't match the `Cargo.lock` or the registry index
+    /// and candidate vendored crates whose checksums differ from the current ones
+    pub checksum_mismatches: Vec<ChecksumMismatch>,
+    /// Repository changes of the updated crates
+    pub repository_changes: Vec<RepositoryChange>,
+    /// License changes of the updated and added crates
+    pub license_diffs: Vec<LicenseDiff>,
+    /// Source changes of the updated crates
+    pub source_diffs: Vec<SourceDiff>,
+    /// Public API changes of the updated crates
+    pub api_diffs: Vec<ApiDiff>,
+    /// Structural red flags of the updated and added crate packages
+    pub package_anomalies: Vec<PackageAnomalies>,
+    /// Unpacked size changes of the changed crates
+    pub size_report: Option<SizeReport>,
+}
+
+impl DiffReport {
+    pub fn new(dependency_diffs: IndexMap<String, Vec<DependencyDiff>>) -> Self {
+        Self {
+            dependency_diffs,
+            checksum_mismatches: vec![],
+            repository_changes: vec![],
+            license_diffs: vec![],
+            source_diffs: vec![],
+            api_diffs: vec![],
+            package_anomalies: vec![],
+            size_report: None,
+        }
+    }
+
+    /// Returns unique diffs of all direct and nested dependencies of all workspace targets
+    pub fn crate_diffs(&self) -> BTreeSet<&CrateDiffInfo> {
+        let mut crate_diffs = BTreeSet::new();
+        for diffs in self.dependency_diffs.values() {
+            for diff in diffs {
+                crate_diffs.insert(&diff.diff);
+                crate_diffs.extend(&diff.updated_deps);
+                crate_diffs.extend(&diff.added_deps);
+                crate_diffs.extend(&diff.removed_deps);
+            }
+        }
+        crate_diffs
+    }
 }
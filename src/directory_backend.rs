@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use semver::Version;
+
+use crate::registry_backend::{CrateMetadata, RegistryBackend, VersionInfo};
+use crate::registry_source::RegistrySource;
+use crate::vendor_dir::{VendorCrate, VendorDir};
+
+/// Registry backend on top of the cargo directory source, e.g. created by `cargo vendor`.
+/// Crates are already unpacked and there are no `.crate` archives. Never accesses the network.
+pub struct DirectoryBackend {
+    /// Registry that is replaced with the directory
+    registry: RegistrySource,
+    /// Directory source folder
+    path: PathBuf,
+}
+
+impl DirectoryBackend {
+    pub fn new(registry: RegistrySource, path: &Path) -> Self {
+        Self {
+            registry,
+            path: path.into(),
+        }
+    }
+
+    fn find(&self, crate_name: &str, version: &Version) -> Result<VendorCrate> {
+        VendorDir::read_crate_versions(&self.path, crate_name)?
+            .into_iter()
+            .find(|c| c.version == *version)
+            .ok_or_else(|| {
+                anyhow!(
+                    "'{crate_name}@{version}' crate is not found in the directory {:?}",
+                    self.path
+                )
+            })
+    }
+}
+
+impl RegistryBackend for DirectoryBackend {
+    fn list_versions(&self, crate_name: &str) -> Result<Vec<VersionInfo>> {
+        let crates = VendorDir::read_crate_versions(&self.path, crate_name)?;
+        if crates.is_empty() {
+            return Err(anyhow!(
+                "'{crate_name}' crate is not found in the directory {:?} of the '{}' registry",
+                self.path,
+                self.registry
+            ));
+        }
+        Ok(crates
+            .into_iter()
+            .map(|c| VersionInfo {
+                version: c.version,
+                yanked: false,
+                checksum: c.checksum,
+            })
+            .collect())
+    }
+
+    fn crate_metadata(&self, crate_name: &str, version: &Version) -> Result<CrateMetadata> {
+        CrateMetadata::read(&self.find(crate_name, version)?.path)
+    }
+
+    fn source_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf> {
+        self.find(crate_name, version).ok().map(|c| c.path)
+    }
+
+    fn download_source(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        self.find(crate_name, version).map(|c| c.path)
+    }
+
+    fn download_archive(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        Err(anyhow!(
+            "Directory {:?} has no archive of the '{crate_name}@{version}' crate",
+            self.path
+        ))
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+
+use semver::Version;
+use serde::Deserialize;
+
+use crate::crate_diff_info::CrateDiffInfo;
+
+/// Git hosting service that provides the commit and comparison pages of the repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Forge {
+    GitHub,
+    /// GitLab.com and self-hosted GitLab including the nested groups
+    GitLab,
+    /// Gitea, Forgejo and Codeberg
+    Gitea,
+    Bitbucket,
+    /// sourcehut (`git.sr.ht`)
+    SourceHut,
+}
+
+impl Forge {
+    /// Detects the forge by the host of the normalized repository URL
+    pub fn detect(repository: &str) -> Option<Self> {
+        let host = repository.strip_prefix("https://")?.split('/').next()?;
+        let host = host.split(':').next()?;
+        match host {
+            "github.com" => Some(Self::GitHub),
+            "gitlab.com" => Some(Self::GitLab),
+            "codeberg.org" | "gitea.com" => Some(Self::Gitea),
+            "bitbucket.org" => Some(Self::Bitbucket),
+            "git.sr.ht" => Some(Self::SourceHut),
+            _ if host.starts_with("gitlab.") => Some(Self::GitLab),
+            _ if host.starts_with("gitea.") || host.starts_with("forgejo.") => Some(Self::Gitea),
+            _ => None,
+        }
+    }
+
+    pub fn commit_url(&self, repository: &str, hash: &str) -> String {
+        match self {
+            Self::GitHub | Self::Gitea | Self::SourceHut => format!("{repository}/commit/{hash}"),
+            Self::GitLab => format!("{repository}/-/commit/{hash}"),
+            Self::Bitbucket => format!("{repository}/commits/{hash}"),
+        }
+    }
+
+    /// Changes between 2 commits. sourcehut has no comparison page so the log of the target commit is used
+    pub fn compare_url(&self, repository: &str, from_hash: &str, to_hash: &str) -> String {
+        let from_hash = short_hash(from_hash);
+        let to_hash = short_hash(to_hash);
+        match self {
+            Self::GitHub | Self::Gitea => format!("{repository}/compare/{from_hash}...{to_hash}"),
+            Self::GitLab => format!("{repository}/-/compare/{from_hash}...{to_hash}"),
+            // Bitbucket shows changes of the first commit that are not in the second one
+            Self::Bitbucket => format!("{repository}/branches/compare/{to_hash}%0D{from_hash}"),
+            Self::SourceHut => format!("{repository}/log/{to_hash}"),
+        }
+    }
+}
+
+/// Link templates of the self-hosted forge. Supported placeholders:
+/// - `{repository}`, `{host}`, `{owner}` (including the nested groups), `{repo}`, `{path_in_vcs}`
+/// - `{hash}`, `{version}` in the commit template
+/// - `{from_hash}`, `{to_hash}`, `{from_version}`, `{to_version}` in the compare template
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ForgeTemplates {
+    pub commit: Option<String>,
+    pub compare: Option<String>,
+}
+
+/// Builds commit and compare links with the configured templates of the host.
+/// Falls back to the built-in forges
+#[derive(Debug, Default)]
+pub struct ForgeLinks {
+    /// Templates by the repository host (with the port if any)
+    templates: HashMap<String, ForgeTemplates>,
+}
+
+impl ForgeLinks {
+    pub fn new(templates: HashMap<String, ForgeTemplates>) -> Self {
+        Self { templates }
+    }
+
+    /// Link to the commit of the crate version
+    pub fn commit_link(
+        &self,
+        diff: &CrateDiffInfo,
+        hash: Option<&str>,
+        version: Option<&Version>,
+    ) -> Option<String> {
+        let repository = diff.repository.as_deref()?;
+        let hash = hash?;
+        if let Some(template) = self.template(repository, |t| t.commit.as_deref()) {
+            return Some(render(
+                template,
+                repository,
+                diff,
+                &[
+                    ("hash", hash.into()),
+                    (
+                        "version",
+                        version.map(|v| v.to_string()).unwrap_or_default(),
+                    ),
+                ],
+            ));
+        }
+        Some(Forge::detect(repository)?.commit_url(repository, hash))
+    }
+
+    /// Link to the changes between the initial and target commits of the crate
+    pub fn compare_link(&self, diff: &CrateDiffInfo) -> Option<String> {
+        let repository = diff.repository.as_deref()?;
+        let from_hash = diff.from_hash.as_deref()?;
+        let to_hash = diff.to_hash.as_deref()?;
+        if let Some(template) = self.template(repository, |t| t.compare.as_deref()) {
+            let version = |version: &Option<Version>| {
+                version.as_ref().map(|v| v.to_string()).unwrap_or_default()
+            };
+            return Some(render(
+                template,
+                repository,
+                diff,
+                &[
+                    ("from_hash", from_hash.into()),
+                    ("to_hash", to_hash.into()),
+                    ("from_version", version(&diff.from_version)),
+                    ("to_version", version(&diff.to_version)),
+                ],
+            ));
+        }
+        Some(Forge::detect(repository)?.compare_url(repository, from_hash, to_hash))
+    }
+
+    fn template(
+        &self,
+        repository: &str,
+        kind: impl Fn(&ForgeTemplates) -> Option<&str>,
+    ) -> Option<&str> {
+        let (host, _) = split_repository(repository)?;
+        kind(self.templates.get(host)?)
+    }
+}
+
+/// Splits the normalized repository URL into the host and the repository path
+fn split_repository(repository: &str) -> Option<(&str, &str)> {
+    repository.strip_prefix("https://")?.split_once('/')
+}
+
+/// Replaces placeholders of the template with the repository, crate and link specific values
+fn render(
+    template: &str,
+    repository: &str,
+    diff: &CrateDiffInfo,
+    values: &[(&str, String)],
+) -> String {
+    let (host, path) = split_repository(repository).unwrap_or_default();
+    let (owner, repo) = path.rsplit_once('/').unwrap_or(("", path));
+    let common = [
+        ("repository", repository.to_string()),
+        ("host", host.into()),
+        ("owner", owner.into()),
+        ("repo", repo.into()),
+        ("path_in_vcs", diff.path_in_vcs.clone().unwrap_or_default()),
+    ];
+
+    let mut link = template.to_string();
+    for (name, value) in common.iter().chain(values) {
+        link = link.replace(&format!("{{{name}}}"), value);
+    }
+    link
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..7).unwrap_or(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use semver::Version;
+
+    use crate::crate_diff_info::CrateDiffInfo;
+    use crate::forge::{Forge, ForgeLinks, ForgeTemplates};
+    use crate::registry_source::RegistrySource;
+
+    fn crate_diff(repository: &str) -> CrateDiffInfo {
+        CrateDiffInfo {
+            name: "foo".into(),
+            from_version: Some(Version::new(1, 0, 0)),
+            from_hash: Some("1234567890abcdef".into()),
+            to_version: Some(Version::new(1, 1, 0)),
+            to_hash: Some("abcdef1234567890".into()),
+            from_yanked: false,
+            to_yanked: false,
+            repository: Some(repository.into()),
+            from_repository: Some(repository.into()),
+            to_repository: Some(repository.into()),
+            from_homepage: None,
+            to_homepage: None,
+            path_in_vcs: Some("crates/foo".into()),
+            registry: RegistrySource::CratesIo,
+        }
+    }
+
+    #[test]
+    fn test_forge_links() {
+        let links = ForgeLinks::default();
+        for (repository, forge, commit, compare) in [
+            (
+                "https://github.com/org/repo",
+                Forge::GitHub,
+                "https://github.com/org/repo/commit/1234567890abcdef",
+                "https://github.com/org/repo/compare/1234567...abcdef1",
+            ),
+            (
+                "https://gitlab.com/group/subgroup/repo",
+                Forge::GitLab,
+                "https://gitlab.com/group/subgroup/repo/-/commit/1234567890abcdef",
+                "https://gitlab.com/group/subgroup/repo/-/compare/1234567...abcdef1",
+            ),
+            (
+                "https://codeberg.org/org/repo",
+                Forge::Gitea,
+                "https://codeberg.org/org/repo/commit/1234567890abcdef",
+                "https://codeberg.org/org/repo/compare/1234567...abcdef1",
+            ),
+            (
+                "https://bitbucket.org/org/repo",
+                Forge::Bitbucket,
+                "https://bitbucket.org/org/repo/commits/1234567890abcdef",
+                "https://bitbucket.org/org/repo/branches/compare/abcdef1%0D1234567",
+            ),
+            (
+                "https://git.sr.ht/~user/repo",
+                Forge::SourceHut,
+                "https://git.sr.ht/~user/repo/commit/1234567890abcdef",
+                "https://git.sr.ht/~user/repo/log/abcdef1",
+            ),
+        ] {
+            let diff = crate_diff(repository);
+            assert_eq!(Some(forge), Forge::detect(repository), "{repository}");
+            assert_eq!(
+                Some(commit.to_string()),
+                links.commit_link(&diff, diff.from_hash.as_deref(), None)
+            );
+            assert_eq!(Some(compare.to_string()), links.compare_link(&diff));
+        }
+
+        assert_eq!(
+            Some(Forge::GitLab),
+            Forge::detect("https://gitlab.example.com/org/repo")
+        );
+        assert_eq!(None, Forge::detect("https://git.example.com/org/repo"));
+        let diff = crate_diff("https://git.example.com/org/repo");
+        assert_eq!(
+            None,
+            links.commit_link(&diff, diff.from_hash.as_deref(), None)
+        );
+        assert_eq!(None, links.commit_link(&diff, None, None));
+    }
+
+    #[test]
+    fn test_forge_templates() {
+        let links = ForgeLinks::new(HashMap::from([(
+            "git.example.com".to_string(),
+            ForgeTemplates {
+                commit: Some("https://git.example.com/{owner}/{repo}/-/tree/{hash}/{path_in_vcs}?v={version}".into()),
+                compare: Some(
+                    "https://{host}/{owner}/{repo}/-/compare/{from_hash}...{to_hash}?from={from_version}&to={to_version}"
+                        .into(),
+                ),
+            },
+        )]));
+
+        let diff = crate_diff("https://git.example.com/group/subgroup/repo");
+        assert_eq!(
+            Some(
+                "https://git.example.com/group/subgroup/repo/-/tree/abcdef1234567890/crates/foo?v=1.1.0"
+                    .to_string()
+            ),
+            links.commit_link(&diff, diff.to_hash.as_deref(), diff.to_version.as_ref())
+        );
+        assert_eq!(
+            Some(
+                "https://git.example.com/group/subgroup/repo/-/compare/1234567890abcdef...abcdef1234567890?from=1.0.0&to=1.1.0"
+                    .to_string()
+            ),
+            links.compare_link(&diff)
+        );
+
+        // built-in forges are used for the other hosts
+        let diff = crate_diff("https://github.com/org/repo");
+        assert_eq!(
+            Some("https://github.com/org/repo/compare/1234567...abcdef1".to_string()),
+            links.compare_link(&diff)
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result, anyhow};
+use cargo_metadata::MetadataCommand;
+use semver::Version;
+
+use crate::crate_archive::move_unpacked;
+use crate::git_source::{GitReference, GitSource};
+
+/// Marker file that is created after the checkout is complete. The same as the unpacked crate marker
+const CHECKOUT_READY_FILE: &str = ".cargo-ok";
+
+/// Cargo git dependencies cache: repository databases in `$CARGO_HOME/git/db`
+/// and commit checkouts in `$CARGO_HOME/git/checkouts`
+pub struct GitCheckouts {
+    /// Path to the cargo git cache: `$CARGO_HOME/git`
+    git_path: PathBuf,
+    /// Do not fetch the repositories
+    offline: bool,
+}
+
+impl GitCheckouts {
+    pub fn new(cargo_home: &Path, offline: bool) -> Self {
+        Self {
+            git_path: cargo_home.join("git"),
+            offline,
+        }
+    }
+
+    /// Newest commit of the tracked reference.
+    /// The repository database is fetched the same way cargo does it unless in offline mode.
+    pub fn latest_commit(&self, source: &GitSource) -> Result<String> {
+        let db_path = self.git_path.join("db").join(source.ident());
+        if !db_path.exists() {
+            return Err(anyhow!(
+                "Repository database of the '{source}' is not found: {db_path:?}"
+            ));
+        }
+
+        let (refspec, local_ref) = match &source.reference {
+            GitReference::DefaultBranch => (
+                Some("+HEAD:refs/remotes/origin/HEAD".to_string()),
+                "refs/remotes/origin/HEAD".to_string(),
+            ),
+            GitReference::Branch(branch) => (
+                Some(format!("+refs/heads/{branch}:refs/remotes/origin/{branch}")),
+                format!("refs/remotes/origin/{branch}"),
+            ),
+            GitReference::Tag(tag) => (
+                Some(format!("+refs/tags/{tag}:refs/remotes/origin/tags/{tag}")),
+                format!("refs/remotes/origin/tags/{tag}"),
+            ),
+            // pinned revision never changes
+            GitReference::Rev(rev) => (None, rev.clone()),
+        };
+
+        if !self.offline
+            && let Some(refspec) = refspec
+        {
+            run_git(
+                &db_path,
+                ["fetch", "--quiet", "--force", &source.url, &refspec],
+            )?;
+        }
+        let commit = run_git(&db_path, ["rev-parse", &format!("{local_ref}^{{commit}}")])?;
+        Ok(String::from_utf8(commit)?.trim().into())
+    }
+
+    /// Path to the repository checkout of the commit.
+    /// Uses cargo checkout if it exists, otherwise extracts the commit from the repository database
+    /// into the temporary folder.
+    pub fn checkout(&self, source: &GitSource, commit: &str) -> Result<PathBuf> {
+        let ident = source.ident();
+        let cargo_checkout = self
+            .git_path
+            .join("checkouts")
+            .join(&ident)
+            .join(&commit[..commit.len().min(7)]);
+        if cargo_checkout.join(CHECKOUT_READY_FILE).exists() {
+            return Ok(cargo_checkout);
+        }
+
+        let checkout = std::env::temp_dir()
+            .join("cargo-ddd")
+            .join("git")
+            .join(&ident)
+            .join(commit);
+        if checkout.join(CHECKOUT_READY_FILE).exists() {
+            return Ok(checkout);
+        }
+
+        let db_path = self.git_path.join("db").join(&ident);
+        let archive = run_git(&db_path, ["archive", "--format=tar", commit])
+            .with_context(|| format!("Cannot extract commit {commit} of the '{source}'"))?;
+        let checkouts_path = checkout.parent().unwrap_or(&checkout);
+        std::fs::create_dir_all(checkouts_path)?;
+        // extract into the private folder first so the concurrent readers never see a partial checkout
+        let extract_dir = tempfile::Builder::new()
+            .prefix(".extract-")
+            .tempdir_in(checkouts_path)?;
+        tar::Archive::new(archive.as_slice()).unpack(extract_dir.path())?;
+        std::fs::write(extract_dir.path().join(CHECKOUT_READY_FILE), "")?;
+
+        move_unpacked(extract_dir.path(), &checkout)
+    }
+}
+
+/// Finds the package in the repository checkout. Returns the package folder and its version
+pub fn find_package(checkout: &Path, crate_name: &str) -> Result<(PathBuf, Version)> {
+    let mut manifests = vec![];
+    collect_manifests(checkout, &mut manifests)?;
+
+    for manifest_path in manifests {
+        // 'cargo metadata' resolves the fields inherited from the workspace
+        let Ok(metadata) = MetadataCommand::new()
+            .manifest_path(&manifest_path)
+            .no_deps()
+            .other_options(vec!["--offline".to_string()])
+            .exec()
+        else {
+            continue;
+        };
+        if let Some(package) = metadata.packages.iter().find(|p| p.name == crate_name)
+            && let Some(package_path) = package.manifest_path.parent()
+        {
+            return Ok((package_path.into(), package.version.clone()));
+        }
+    }
+
+    Err(anyhow!(
+        "'{crate_name}' package is not found in the checkout {checkout:?}"
+    ))
+}
+
+/// Collects manifests of the checkout. Root manifest goes first
+fn collect_manifests(dir: &Path, manifests: &mut Vec<PathBuf>) -> Result<()> {
+    let manifest_path = dir.join("Cargo.toml");
+    if manifest_path.is_file() {
+        manifests.push(manifest_path);
+    }
+
+    let mut dirs = vec![];
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Cannot read folder: {dir:?}"))? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        if entry.file_type()?.is_dir() && file_name != ".git" && file_name != "target" {
+            dirs.push(entry.path());
+        }
+    }
+    dirs.sort();
+
+    for dir in dirs {
+        collect_manifests(&dir, manifests)?;
+    }
+    Ok(())
+}
+
+/// Runs git command in the repository database and returns its output
+fn run_git<I, S>(db_path: &Path, args: I) -> Result<Vec<u8>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut cmd = Command::new("git");
+    cmd.arg("--git-dir").arg(db_path);
+    cmd.args(args);
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Cannot run command: {cmd:?}\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,166 @@
+use std::hash::Hasher;
+
+use crate::repository_url::normalize_repository;
+use crate::stable_hasher::StableHasher;
+
+/// Reference tracked by the git dependency
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum GitReference {
+    /// Default branch of the repository
+    #[default]
+    DefaultBranch,
+    Branch(String),
+    Tag(String),
+    /// Pinned revision
+    Rev(String),
+}
+
+/// Git repository of the git dependency
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GitSource {
+    /// Repository URL
+    pub url: String,
+    pub reference: GitReference,
+}
+
+impl std::fmt::Display for GitSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reference {
+            GitReference::DefaultBranch => write!(f, "{}", self.url),
+            GitReference::Branch(branch) => write!(f, "{}?branch={branch}", self.url),
+            GitReference::Tag(tag) => write!(f, "{}?tag={tag}", self.url),
+            GitReference::Rev(rev) => write!(f, "{}?rev={rev}", self.url),
+        }
+    }
+}
+
+impl GitSource {
+    /// Parses the git package path from the cargo metadata: `https://github.com/owner/repo?branch=main`
+    pub fn parse(path: &str) -> Self {
+        let (url, query) = path.split_once('?').unwrap_or((path, ""));
+        let reference = query
+            .split('&')
+            .find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                let value = percent_decode(value);
+                match key {
+                    "branch" => Some(GitReference::Branch(value)),
+                    "tag" => Some(GitReference::Tag(value)),
+                    "rev" => Some(GitReference::Rev(value)),
+                    _ => None,
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            url: url.into(),
+            reference,
+        }
+    }
+
+    /// Normalized repository URL
+    pub fn repository(&self) -> String {
+        normalize_repository(&self.url)
+    }
+
+    /// Name of the repository folders in `$CARGO_HOME/git/db` and `$CARGO_HOME/git/checkouts`
+    /// the same way cargo generates it: `<repository name>-<hash>`
+    pub fn ident(&self) -> String {
+        let url = canonical_url(&self.url);
+        let path = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/').map(|(_, path)| path).unwrap_or(""),
+            None => &url,
+        };
+        let name = path
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("_empty");
+
+        let mut hasher = StableHasher::new();
+        // the same way `str` is hashed
+        hasher.write(url.as_bytes());
+        hasher.write_u8(0xff);
+        let hash = hasher.finish().to_le_bytes();
+        let hash: String = hash.iter().map(|b| format!("{b:02x}")).collect();
+
+        format!("{name}-{hash}")
+    }
+}
+
+/// Repository URL canonicalized the same way cargo does it:
+/// - trailing slash and `.git` suffix are removed
+/// - GitHub URLs use `https` scheme and lowercase path
+fn canonical_url(url: &str) -> String {
+    let mut url = url.trim_end_matches('/').to_string();
+    if let Some((_, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        // skip user info
+        let host = authority
+            .rsplit_once('@')
+            .map(|(_, host)| host)
+            .unwrap_or(authority);
+        if host == "github.com" {
+            url = format!("https://{authority}/{}", path.to_lowercase());
+        }
+    }
+    if let Some(stripped) = url.strip_suffix(".git") {
+        url = stripped.into();
+    }
+    url
+}
+
+/// Decodes `%XX` sequences of the URL query value
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = value
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::git_source::{GitReference, GitSource};
+
+    #[test]
+    fn test_parse_git_source() {
+        let source = GitSource::parse("https://github.com/Foo/Bar.git?branch=feature%2Fx");
+        assert_eq!("https://github.com/Foo/Bar.git", source.url);
+        assert_eq!(GitReference::Branch("feature/x".into()), source.reference);
+        assert_eq!("https://github.com/Foo/Bar", source.repository());
+
+        assert_eq!(
+            GitReference::DefaultBranch,
+            GitSource::parse("https://github.com/foo/bar").reference
+        );
+        assert_eq!(
+            GitReference::Tag("v1.0".into()),
+            GitSource::parse("https://github.com/foo/bar?tag=v1.0").reference
+        );
+    }
+
+    #[test]
+    fn test_ident() {
+        assert_eq!(
+            "repo-a986bbcd496a64a3",
+            GitSource::parse("file:///tmp/gitexp/repo?branch=main").ident()
+        );
+        assert_eq!(
+            GitSource::parse("https://github.com/foo/bar").ident(),
+            GitSource::parse("ssh://github.com/Foo/Bar.git/").ident()
+        );
+    }
+}
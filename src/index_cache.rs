@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use semver::Version;
+use serde::Deserialize;
+
+/// Supported version of the cache file format
+const CACHE_VERSION: u8 = 3;
+
+/// Dependency of the published crate version
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)] // complete index entry format, not all fields are used yet
+pub struct IndexDependency {
+    /// Dependency name. Renamed dependencies have the original name in the `package` field
+    pub name: String,
+    /// Version requirement
+    pub req: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default = "default_true")]
+    pub default_features: bool,
+    /// Target platform of the dependency
+    pub target: Option<String>,
+    /// Dependency kind: `normal`, `dev` or `build`. `None` means `normal`
+    pub kind: Option<String>,
+    /// Index URL of the dependency registry if it's not the same as the crate one
+    pub registry: Option<String>,
+    /// Original name of the renamed dependency
+    pub package: Option<String>,
+}
+
+/// Published crate version from the registry index
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)] // complete index entry format, not all fields are used yet
+pub struct IndexEntry {
+    /// Crate name
+    pub name: String,
+    /// Crate version
+    pub vers: Version,
+    #[serde(default)]
+    pub deps: Vec<IndexDependency>,
+    /// SHA-256 checksum of the `.crate` package
+    pub cksum: String,
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Features with the extended syntax (`dep:` and `?`) that are stored separately
+    #[serde(default)]
+    pub features2: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+#[allow(dead_code)]
+impl IndexEntry {
+    /// All features of the crate version
+    pub fn all_features(&self) -> BTreeMap<String, Vec<String>> {
+        let mut features = self.features.clone();
+        features.extend(self.features2.clone());
+        features
+    }
+}
+
+/// Reader of the cargo index cache: `$CARGO_HOME/registry/index/<registry>/.cache`
+pub struct IndexCache {
+    /// Path to the registry index folder
+    index_path: PathBuf,
+}
+
+impl IndexCache {
+    pub fn new(index_path: PathBuf) -> Self {
+        Self { index_path }
+    }
+
+    /// Reads all published versions of the crate sorted by version.
+    /// Returns `None` if the crate is not in the cache.
+    pub fn read(&self, crate_name: &str) -> Option<Vec<IndexEntry>> {
+        let cache_path = self
+            .index_path
+            .join(".cache")
+            .join(cache_file_path(crate_name));
+        let data = std::fs::read(&cache_path).ok()?;
+
+        match parse_cache_file(&data) {
+            Ok(mut entries) => {
+                entries.sort_by(|a, b| a.vers.cmp(&b.vers));
+                Some(entries)
+            }
+            Err(err) => {
+                eprintln!("[WARN] Cannot read index cache file {cache_path:?}. Error: {err:#}");
+                None
+            }
+        }
+    }
+}
+
+/// Reads all published versions of the crate from the plain index file of the local registry
+/// (`<index_path>/<prefix>/<name>`) sorted by version. Returns `None` if the crate is not in the index.
+pub fn read_index_file(index_path: &Path, crate_name: &str) -> Option<Vec<IndexEntry>> {
+    let file_path = index_path.join(cache_file_path(crate_name));
+    let data = std::fs::read_to_string(file_path).ok()?;
+    let mut entries = parse_index_file(&data);
+    entries.sort_by(|a, b| a.vers.cmp(&b.vers));
+    Some(entries)
+}
+
+/// Index file contains JSON description of one crate version per line
+fn parse_index_file(data: &str) -> Vec<IndexEntry> {
+    data.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(err) => {
+                eprintln!("[WARN] Cannot parse index entry: {line}. Error: {err}");
+                None
+            }
+        })
+        .collect()
+}
+
+/// Relative path of the crate file in the index: `1/a`, `2/ab`, `3/a/abc`, `ab/cd/abcd...`
+fn cache_file_path(crate_name: &str) -> PathBuf {
+    let name = crate_name.to_lowercase();
+    let prefix = match name.len() {
+        1 => PathBuf::from("1"),
+        2 => PathBuf::from("2"),
+        3 => Path::new("3").join(&name[..1]),
+        _ => Path::new(&name[..2]).join(&name[2..4]),
+    };
+    prefix.join(name)
+}
+
+/// Cache file format:
+/// - cache version: u8
+/// - index format version: u32 LE
+/// - index version (e.g. ETag or git commit) followed by 0
+/// - repeated pairs of the crate version and its JSON description each followed by 0
+fn parse_cache_file(data: &[u8]) -> Result<Vec<IndexEntry>> {
+    let (&cache_version, data) = data
+        .split_first()
+        .ok_or_else(|| anyhow!("Empty cache file"))?;
+    if cache_version != CACHE_VERSION {
+        return Err(anyhow!("Unsupported cache version: {cache_version}"));
+    }
+    let data = data
+        .get(4..)
+        .ok_or_else(|| anyhow!("Index format version is missing"))?;
+
+    let mut parts = data.split(|b| *b == 0);
+    // skip index version
+    parts.next();
+
+    let mut entries = vec![];
+    while let Some(version) = parts.next() {
+        if version.is_empty() {
+            // trailing 0
+            break;
+        }
+        let json = parts
+            .next()
+            .ok_or_else(|| anyhow!("Description of the version is missing"))?;
+        match serde_json::from_slice(json) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                eprintln!(
+                    "[WARN] Cannot parse index entry of the version {}. Error: {err}",
+                    String::from_utf8_lossy(version)
+                );
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::index_cache::{cache_file_path, parse_cache_file, parse_index_file};
+
+    #[test]
+    fn test_cache_file_path() {
+        assert_eq!(PathBuf::from("1/a"), cache_file_path("a"));
+        assert_eq!(PathBuf::from("2/ab"), cache_file_path("ab"));
+        assert_eq!(PathBuf::from("3/a/abc"), cache_file_path("abc"));
+        assert_eq!(PathBuf::from("se/rd/serde"), cache_file_path("Serde"));
+    }
+
+    #[test]
+    fn test_parse_cache_file() {
+        let mut data = vec![3, 2, 0, 0, 0];
+        data.extend(b"etag\0");
+        data.extend(b"1.0.0\0");
+        data.extend(br#"{"name":"foo","vers":"1.0.0","deps":[{"name":"bar","req":"^0.1","kind":"dev"}],"cksum":"aa","features":{"std":[]},"yanked":true}"#);
+        data.push(0);
+        data.extend(b"1.1.0\0");
+        data.extend(br#"{"name":"foo","vers":"1.1.0","deps":[],"cksum":"bb","features":{},"features2":{"serde":["dep:serde"]}}"#);
+        data.push(0);
+
+        let entries = parse_cache_file(&data).unwrap();
+        assert_eq!(2, entries.len());
+        assert!(entries[0].yanked);
+        assert_eq!("bar", entries[0].deps[0].name);
+        assert!(entries[0].deps[0].default_features);
+        assert_eq!(Some("dev"), entries[0].deps[0].kind.as_deref());
+        assert!(!entries[1].yanked);
+        assert_eq!("bb", entries[1].cksum);
+        assert!(entries[1].all_features().contains_key("serde"));
+
+        assert!(parse_cache_file(&[1, 2, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_parse_index_file() {
+        let entries = parse_index_file(
+            r#"{"name":"foo","vers":"1.0.0","deps":[],"cksum":"aa","features":{},"yanked":false}
+
+{"name":"foo","vers":"1.1.0","deps":[],"cksum":"bb","features":{},"yanked":true}
+not a json
+"#,
+        );
+        assert_eq!(2, entries.len());
+        assert_eq!("aa", entries[0].cksum);
+        assert!(entries[1].yanked);
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use semver::Version;
+
+use crate::crate_manifest::CrateManifest;
+use crate::diff_report::DiffReport;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// File name prefixes of the license files shipped with the crate
+const LICENSE_FILE_PREFIXES: [&str; 5] = ["LICENSE", "LICENCE", "COPYING", "UNLICENSE", "NOTICE"];
+
+/// License information of the crate version
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicenseInfo {
+    /// SPDX license expression from the `license` manifest field
+    pub license: Option<String>,
+    /// Path from the `license-file` manifest field
+    pub license_file: Option<String>,
+    /// License files shipped with the crate and their content
+    pub files: BTreeMap<String, String>,
+}
+
+/// License change between 2 versions of the crate or license of the added crate
+#[derive(Debug, Clone)]
+pub struct LicenseDiff {
+    /// Crate name
+    pub name: String,
+    /// Initial crate version. `None` for the added crate
+    pub from_version: Option<Version>,
+    /// Target crate version
+    pub to_version: Version,
+    /// Initial crate license expression
+    pub from_license: Option<String>,
+    /// Target crate license expression
+    pub to_license: Option<String>,
+    /// License files that were added, removed or modified
+    pub changed_files: Vec<String>,
+    /// False if the target license left the allowlist
+    pub allowed: bool,
+}
+
+/// Compares licenses of the updated crates and collects licenses of the added ones
+pub struct LicenseChecker<'a> {
+    registry_manager: &'a RegistryManager,
+    /// Allowed SPDX license identifiers. Empty list allows everything
+    allowed_licenses: Vec<String>,
+}
+
+impl<'a> LicenseChecker<'a> {
+    pub fn new(registry_manager: &'a RegistryManager, allowed_licenses: Vec<String>) -> Self {
+        Self {
+            registry_manager,
+            allowed_licenses,
+        }
+    }
+
+    /// Returns license changes of all updated and added crates in the report
+    pub fn check(&self, report: &DiffReport) -> Vec<LicenseDiff> {
+        let mut license_diffs = vec![];
+
+        for diff in report.crate_diffs() {
+            let Some(to_version) = &diff.to_version else {
+                // removed crate
+                continue;
+            };
+            let Some(to_info) = self.get_license_info(
+                &diff.name,
+                to_version,
+                diff.to_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+            let to_allowed = self.is_allowed(to_info.license.as_deref());
+
+            let Some(from_version) = &diff.from_version else {
+                // added crate: always report its license
+                license_diffs.push(LicenseDiff {
+                    name: diff.name.clone(),
+                    from_version: None,
+                    to_version: to_version.clone(),
+                    from_license: None,
+                    to_license: to_info.license,
+                    changed_files: to_info.files.into_keys().collect(),
+                    allowed: to_allowed,
+                });
+                continue;
+            };
+            let Some(from_info) = self.get_license_info(
+                &diff.name,
+                from_version,
+                diff.from_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+            if from_info == to_info {
+                continue;
+            }
+
+            // license is considered left the allowlist only if it was allowed before
+            let allowed = to_allowed || !self.is_allowed(from_info.license.as_deref());
+
+            license_diffs.push(LicenseDiff {
+                name: diff.name.clone(),
+                from_version: Some(from_version.clone()),
+                to_version: to_version.clone(),
+                changed_files: Self::changed_files(&from_info, &to_info),
+                from_license: from_info.license,
+                to_license: to_info.license,
+                allowed,
+            });
+        }
+
+        license_diffs
+    }
+
+    fn get_license_info(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        hash: Option<&str>,
+        registry: &RegistrySource,
+    ) -> Option<LicenseInfo> {
+        let crate_path = self
+            .registry_manager
+            .fetch_crate(crate_name, version, hash, registry)?;
+        let manifest = match CrateManifest::read(&crate_path) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot get license of the '{crate_name}@{version}' crate. Error: {err:#}"
+                );
+                return None;
+            }
+        };
+
+        Some(LicenseInfo {
+            files: Self::read_license_files(&crate_path, manifest.package.license_file.as_deref()),
+            license: manifest.package.license,
+            license_file: manifest.package.license_file,
+        })
+    }
+
+    /// Reads license files from the crate root folder and the file from the `license-file` manifest field
+    fn read_license_files(
+        crate_path: &Path,
+        license_file: Option<&str>,
+    ) -> BTreeMap<String, String> {
+        let mut files = BTreeMap::new();
+
+        if let Ok(entries) = std::fs::read_dir(crate_path) {
+            for entry in entries.flatten() {
+                let Ok(file_name) = entry.file_name().into_string() else {
+                    continue;
+                };
+                let upper_name = file_name.to_uppercase();
+                if LICENSE_FILE_PREFIXES
+                    .iter()
+                    .any(|prefix| upper_name.starts_with(prefix))
+                    && let Ok(content) = read_crate_file(crate_path, &file_name)
+                {
+                    files.insert(file_name, content);
+                }
+            }
+        }
+
+        if let Some(license_file) = license_file
+            && !files.contains_key(license_file)
+        {
+            match read_crate_file(crate_path, license_file) {
+                Ok(content) => {
+                    files.insert(license_file.into(), content);
+                }
+                Err(err) => {
+                    eprintln!("[WARN] Cannot read license file '{license_file}'. Error: {err:#}");
+                }
+            }
+        }
+
+        files
+    }
+
+    fn changed_files(from_info: &LicenseInfo, to_info: &LicenseInfo) -> Vec<String> {
+        let mut changed_files: Vec<_> = from_info
+            .files
+            .iter()
+            .filter(|(name, content)| to_info.files.get(*name) != Some(*content))
+            .map(|(name, _)| name.clone())
+            .collect();
+        changed_files.extend(
+            to_info
+                .files
+                .keys()
+                .filter(|name| !from_info.files.contains_key(*name))
+                .cloned(),
+        );
+        changed_files.sort();
+        changed_files
+    }
+
+    fn is_allowed(&self, license: Option<&str>) -> bool {
+        if self.allowed_licenses.is_empty() {
+            return true;
+        }
+        license.is_some_and(|license| is_license_allowed(license, &self.allowed_licenses))
+    }
+}
+
+/// Evaluates SPDX license expression against the list of allowed license identifiers.
+/// `OR` requires any of the operands to be allowed and `AND` requires all of them.
+/// Legacy `/` separator is treated as `OR`.
+fn is_license_allowed(expression: &str, allowed_licenses: &[String]) -> bool {
+    let expression = expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace('/', " OR ");
+    let tokens: Vec<_> = expression.split_whitespace().collect();
+    let mut pos = 0;
+
+    eval_or_expr(&tokens, &mut pos, allowed_licenses) == Some(true) && pos == tokens.len()
+}
+
+fn eval_or_expr(tokens: &[&str], pos: &mut usize, allowed_licenses: &[String]) -> Option<bool> {
+    let mut result = eval_and_expr(tokens, pos, allowed_licenses)?;
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("OR"))
+    {
+        *pos += 1;
+        result |= eval_and_expr(tokens, pos, allowed_licenses)?;
+    }
+    Some(result)
+}
+
+fn eval_and_expr(tokens: &[&str], pos: &mut usize, allowed_licenses: &[String]) -> Option<bool> {
+    let mut result = eval_license(tokens, pos, allowed_licenses)?;
+    while tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("AND"))
+    {
+        *pos += 1;
+        result &= eval_license(tokens, pos, allowed_licenses)?;
+    }
+    Some(result)
+}
+
+fn eval_license(tokens: &[&str], pos: &mut usize, allowed_licenses: &[String]) -> Option<bool> {
+    let token = *tokens.get(*pos)?;
+    *pos += 1;
+
+    if token == "(" {
+        let result = eval_or_expr(tokens, pos, allowed_licenses)?;
+        if tokens.get(*pos) != Some(&")") {
+            return None;
+        }
+        *pos += 1;
+        return Some(result);
+    }
+
+    if tokens
+        .get(*pos)
+        .is_some_and(|t| t.eq_ignore_ascii_case("WITH"))
+    {
+        // skip license exception
+        tokens.get(*pos + 1)?;
+        *pos += 2;
+    }
+
+    Some(allowed_licenses.iter().any(|l| l == token))
+}
+
+/// Reads the file of the crate. Paths from the untrusted manifest and symbolic links may never leave the crate folder
+fn read_crate_file(crate_path: &Path, file: &str) -> Result<String> {
+    let crate_path = crate_path.canonicalize()?;
+    let file_path = crate_path.join(file).canonicalize()?;
+    if !file_path.starts_with(&crate_path) {
+        return Err(anyhow!(
+            "File is outside of the crate folder: {file_path:?}"
+        ));
+    }
+    Ok(std::fs::read_to_string(file_path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::license_checker::{LicenseChecker, is_license_allowed};
+
+    #[test]
+    fn test_single_license() {
+        let allowed = vec!["MIT".to_string()];
+        assert!(is_license_allowed("MIT", &allowed));
+        assert!(!is_license_allowed("Apache-2.0", &allowed));
+    }
+
+    #[test]
+    fn test_or_expression() {
+        let allowed = vec!["MIT".to_string()];
+        assert!(is_license_allowed("MIT OR Apache-2.0", &allowed));
+        assert!(is_license_allowed("Apache-2.0 OR MIT", &allowed));
+        assert!(is_license_allowed("MIT/Apache-2.0", &allowed));
+        assert!(!is_license_allowed("GPL-3.0 OR Apache-2.0", &allowed));
+    }
+
+    #[test]
+    fn test_and_expression() {
+        let allowed = vec!["MIT".to_string(), "Unicode-3.0".to_string()];
+        assert!(is_license_allowed("MIT AND Unicode-3.0", &allowed));
+        assert!(!is_license_allowed("MIT AND GPL-3.0", &allowed));
+        assert!(is_license_allowed(
+            "(MIT OR Apache-2.0) AND Unicode-3.0",
+            &allowed
+        ));
+        assert!(!is_license_allowed(
+            "(GPL-3.0 OR BSD-3-Clause) AND MIT",
+            &allowed
+        ));
+    }
+
+    #[test]
+    fn test_with_exception() {
+        let allowed = vec!["Apache-2.0".to_string()];
+        assert!(is_license_allowed(
+            "Apache-2.0 WITH LLVM-exception",
+            &allowed
+        ));
+        assert!(!is_license_allowed("Apache-2.0 WITH", &allowed));
+    }
+
+    #[test]
+    fn test_malformed_expression() {
+        let allowed = vec!["MIT".to_string()];
+        assert!(!is_license_allowed("(MIT", &allowed));
+        assert!(!is_license_allowed("MIT OR", &allowed));
+        assert!(!is_license_allowed("", &allowed));
+    }
+
+    #[test]
+    fn test_read_license_files() {
+        let outside_dir = tempfile::tempdir().unwrap();
+        std::fs::write(outside_dir.path().join("secret"), "secret").unwrap();
+        let crate_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(crate_dir.path().join("legal")).unwrap();
+        std::fs::write(crate_dir.path().join("LICENSE-MIT"), "MIT").unwrap();
+        std::fs::write(crate_dir.path().join("legal/TERMS"), "terms").unwrap();
+
+        let files = LicenseChecker::read_license_files(crate_dir.path(), Some("legal/TERMS"));
+        assert_eq!(
+            vec!["LICENSE-MIT", "legal/TERMS"],
+            files.keys().collect::<Vec<_>>()
+        );
+
+        let secret_path = outside_dir.path().join("secret");
+        let relative_path = format!(
+            "../{}/secret",
+            outside_dir.path().file_name().unwrap().to_string_lossy()
+        );
+        for license_file in [secret_path.to_str().unwrap(), &relative_path] {
+            let files = LicenseChecker::read_license_files(crate_dir.path(), Some(license_file));
+            assert_eq!(vec!["LICENSE-MIT"], files.keys().collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use semver::Version;
+
+use crate::cargo_cache_backend::registry_dir_name;
+use crate::crate_archive::{unpack_crate, unpacked_crate};
+use crate::index_cache::read_index_file;
+use crate::registry_backend::{CrateMetadata, RegistryBackend, VersionInfo};
+use crate::registry_source::RegistrySource;
+
+/// Registry backend on top of the cargo local registry: `<path>/index` and `<path>/<name>-<version>.crate` files.
+/// Crate sources are unpacked into the temporary folder. Never accesses the network.
+pub struct LocalRegistryBackend {
+    /// Registry that is replaced with the local one
+    registry: RegistrySource,
+    /// Local registry root folder
+    path: PathBuf,
+    /// Folder the crates are unpacked to
+    unpack_path: PathBuf,
+}
+
+impl LocalRegistryBackend {
+    pub fn new(registry: RegistrySource, path: &Path) -> Self {
+        let unpack_path = std::env::temp_dir()
+            .join("cargo-ddd")
+            .join(registry_dir_name(&format!("file://{}", path.display())));
+
+        Self {
+            registry,
+            path: path.into(),
+            unpack_path,
+        }
+    }
+}
+
+impl RegistryBackend for LocalRegistryBackend {
+    fn list_versions(&self, crate_name: &str) -> Result<Vec<VersionInfo>> {
+        let entries = read_index_file(&self.path.join("index"), crate_name).ok_or_else(|| {
+            anyhow!(
+                "'{crate_name}' crate is not found in the local registry {:?} of the '{}' registry",
+                self.path,
+                self.registry
+            )
+        })?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| VersionInfo {
+                version: entry.vers,
+                yanked: entry.yanked,
+                checksum: Some(entry.cksum),
+            })
+            .collect())
+    }
+
+    fn crate_metadata(&self, crate_name: &str, version: &Version) -> Result<CrateMetadata> {
+        let crate_path = match self.source_path(crate_name, version) {
+            Some(crate_path) => crate_path,
+            None => self.download_source(crate_name, version)?,
+        };
+        CrateMetadata::read(&crate_path)
+    }
+
+    fn source_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf> {
+        unpacked_crate(&self.unpack_path, &format!("{crate_name}-{version}"))
+    }
+
+    /// Unpacks the archive of the local registry into the temporary folder
+    fn download_source(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        let archive_path = self.download_archive(crate_name, version)?;
+        let archive = std::fs::File::open(&archive_path)?;
+        unpack_crate(
+            archive,
+            &self.unpack_path,
+            &format!("{crate_name}-{version}"),
+        )
+        .with_context(|| format!("Cannot unpack '{crate_name}@{version}' crate"))
+    }
+
+    fn download_archive(&self, crate_name: &str, version: &Version) -> Result<PathBuf> {
+        let archive_path = self.path.join(format!("{crate_name}-{version}.crate"));
+        if archive_path.exists() {
+            Ok(archive_path)
+        } else {
+            Err(anyhow!(
+                "Cannot find '{crate_name}@{version}' crate archive in the local registry {:?}",
+                self.path
+            ))
+        }
+    }
+}
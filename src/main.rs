@@ -1,34 +1,76 @@
+mod api_comparator;
+mod cargo_cache_backend;
+mod cargo_config;
+mod cargo_lock;
 mod cargo_meta;
 mod cargo_runner;
+mod checksum_verifier;
 mod cli;
+mod config;
+mod crate_archive;
 mod crate_diff_builder;
 mod crate_diff_info;
 mod crate_diff_request;
+mod crate_files;
 mod crate_info;
+mod crate_manifest;
+mod crate_prefetch;
+mod crates_io_backend;
 mod dependency_diff;
 mod diff_report;
+mod directory_backend;
 mod field_size;
+mod forge;
+mod git_checkouts;
+mod git_source;
+mod index_cache;
+mod license_checker;
+mod local_registry_backend;
 mod package_id_info;
+mod package_inspector;
 mod package_source;
+mod path_filter;
+mod persistent_cache;
+mod registry_backend;
 mod registry_manager;
+mod registry_source;
+mod repository_checker;
+mod repository_url;
 mod simple_report_printer;
+mod size_comparator;
+mod source_comparator;
+mod stable_hasher;
+mod vcs_info;
+mod vendor_dir;
 mod verbose_report_printer;
+mod worker_pool;
 
-use std::cmp::Ordering;
+use std::collections::BTreeSet;
 
 use anyhow::{Result, anyhow};
 use clap::Parser;
-use indexmap::IndexMap;
 
+use crate::api_comparator::ApiComparator;
+use crate::cargo_lock::CargoLock;
 use crate::cargo_meta::CargoMeta;
+use crate::checksum_verifier::ChecksumVerifier;
 use crate::cli::Cli;
+use crate::config::Config;
 use crate::crate_diff_builder::CrateDiffBuilder;
-use crate::crate_diff_info::CrateDiffInfo;
-use crate::dependency_diff::DependencyDiff;
 use crate::diff_report::DiffReport;
+use crate::forge::ForgeLinks;
+use crate::license_checker::LicenseChecker;
+use crate::package_inspector::PackageInspector;
+use crate::path_filter::PathFilter;
+use crate::persistent_cache::PersistentCache;
 use crate::registry_manager::RegistryManager;
+use crate::repository_checker::check_repositories;
 use crate::simple_report_printer::SimpleReportPrinter;
+use crate::size_comparator::SizeComparator;
+use crate::source_comparator::SourceComparator;
+use crate::vendor_dir::VendorDir;
 use crate::verbose_report_printer::VerboseReportPrinter;
+use crate::worker_pool::WorkerPool;
 
 fn main() -> Result<()> {
     let mut cli = Cli::parse();
@@ -39,19 +81,71 @@ fn main() -> Result<()> {
         cli.crates.remove(0);
     }
 
-    let need_local_metadata =
-        cli.crates.is_empty() || cli.crates.iter().any(|c| c.from_version.is_none());
+    let config = Config::load(cli.config.as_deref(), &cli.manifest_path)?;
+
+    let need_local_metadata = cli.candidate_vendor_dir.is_none()
+        && (cli.crates.is_empty() || cli.crates.iter().any(|c| c.from_version.is_none()));
     let cargo_meta = if need_local_metadata {
-        Some(CargoMeta::new(&cli.manifest_path)?)
+        Some(CargoMeta::new(&cli.manifest_path, cli.offline)?)
     } else {
         None
     };
 
-    let registry_path = cargo_meta.as_ref().and_then(|cm| cm.registry_path());
-    let registry_manager = RegistryManager::new(registry_path)?;
-    let mut diff_builder = CrateDiffBuilder::new(registry_manager, cli.diff_rs && !cli.verbose);
+    let mut registry_manager = RegistryManager::new(cli.offline)?;
+    if let Some(crates_io_api) = &cli.crates_io_api {
+        registry_manager.use_crates_io_api(crates_io_api);
+    }
+    if cli.pre {
+        registry_manager.allow_pre_releases();
+    }
+    if !cli.no_cache {
+        registry_manager.use_persistent_cache(PersistentCache::new(
+            PersistentCache::default_path()?,
+            cli.refresh,
+        ));
+    }
+    if cli.verify_checksums {
+        let cargo_lock = match &cargo_meta {
+            Some(cargo_meta) => CargoLock::read(&cargo_meta.workspace_root().join("Cargo.lock"))?,
+            None => CargoLock::default(),
+        };
+        registry_manager.verify_checksums(cargo_lock)?;
+    }
+
+    let vendor_dir = cli
+        .vendor_dir
+        .clone()
+        .or_else(|| registry_manager.detect_vendor_dir())
+        .map(|path| VendorDir::read(&path))
+        .transpose()?;
+    let candidate_vendor_dir = cli
+        .candidate_vendor_dir
+        .as_deref()
+        .map(VendorDir::read)
+        .transpose()?;
+    for vendor_dir in vendor_dir.iter().chain(&candidate_vendor_dir) {
+        registry_manager.add_vendor_dir(vendor_dir.clone());
+    }
+
+    let diff_builder = CrateDiffBuilder::new(
+        registry_manager,
+        cli.registry.clone(),
+        cli.diff_rs && !cli.verbose,
+        cli.jobs.unwrap_or_else(WorkerPool::default_jobs),
+    );
 
-    let mut target_version_diffs = if cli.crates.is_empty() {
+    let mut vendor_mismatches = vec![];
+    let mut target_version_diffs = if let Some(candidate_vendor_dir) = &candidate_vendor_dir {
+        // compare current vendor directory with the candidate one
+        let Some(vendor_dir) = &vendor_dir else {
+            return Err(anyhow!(
+                "Current vendor directory is not found. Provide it with '--vendor-dir'."
+            ));
+        };
+
+        vendor_mismatches = vendor_dir.checksum_mismatches(candidate_vendor_dir);
+        diff_builder.build_from_vendor_dirs(vendor_dir, candidate_vendor_dir)
+    } else if cli.crates.is_empty() {
         // if no crates are provided in cli, use local crate dependencies that need an update
         let Some(cargo_meta) = cargo_meta else {
             return Err(anyhow!(
@@ -73,7 +167,7 @@ fn main() -> Result<()> {
         diff_builder.build_from_crates(&cli.crates)
     };
 
-    if target_version_diffs.is_empty() {
+    if target_version_diffs.is_empty() && vendor_mismatches.is_empty() {
         println!("All crates are up to date.");
         return Ok(());
     }
@@ -81,48 +175,93 @@ fn main() -> Result<()> {
     target_version_diffs.sort_keys();
 
     // generate diff links
-    let dependency_diffs: IndexMap<_, _> = target_version_diffs
-        .into_iter()
-        .map(|(target_name, diffs)| {
-            let dep_diff: Vec<_> = diffs
-                .into_iter()
-                .map(|diff| {
-                    let (mut removed_deps, mut added_deps, mut updated_deps) = if cli.show_all {
-                        diff_builder.build_nested_deps(&diff)
-                    } else {
-                        Default::default()
-                    };
-
-                    removed_deps.sort_by(compare_diffs);
-                    added_deps.sort_by(compare_diffs);
-                    updated_deps.sort_by(compare_diffs);
-
-                    DependencyDiff {
-                        diff,
-                        removed_deps,
-                        added_deps,
-                        updated_deps,
-                    }
-                })
-                .collect();
-            (target_name, dep_diff)
-        })
-        .collect();
+    let dependency_diffs = diff_builder.build_dependency_diffs(target_version_diffs, cli.show_all);
 
-    let diff_report = DiffReport { dependency_diffs };
+    let mut diff_report = DiffReport::new(dependency_diffs);
+    diff_report.checksum_mismatches = vendor_mismatches;
+    diff_report.repository_changes = check_repositories(&diff_report);
+
+    let analyze_sources = cli.licenses
+        || !cli.allowed_licenses.is_empty()
+        || cli.stats
+        || cli.source_diff
+        || cli.api
+        || cli.anomalies
+        || cli.size
+        || cli.size_threshold.is_some();
+    if analyze_sources {
+        diff_builder.prefetch_report(&diff_report);
+    }
+
+    if cli.verify_checksums {
+        let checksum_verifier = ChecksumVerifier::new(diff_builder.registry_manager());
+        let mismatches = checksum_verifier.verify(&diff_report);
+        diff_report.checksum_mismatches.extend(mismatches);
+    }
 
+    if cli.licenses || !cli.allowed_licenses.is_empty() {
+        let license_checker =
+            LicenseChecker::new(diff_builder.registry_manager(), cli.allowed_licenses);
+        diff_report.license_diffs = license_checker.check(&diff_report);
+    }
+
+    if cli.stats || cli.source_diff {
+        let source_comparator = SourceComparator::new(
+            diff_builder.registry_manager(),
+            &config,
+            PathFilter::new(cli.include, cli.exclude),
+            cli.source_diff,
+        );
+        diff_report.source_diffs = source_comparator.compare(&diff_report);
+    }
+
+    if cli.api {
+        let api_comparator = ApiComparator::new(diff_builder.registry_manager());
+        diff_report.api_diffs = api_comparator.compare(&diff_report);
+    }
+
+    if cli.anomalies {
+        let package_inspector = PackageInspector::new(diff_builder.registry_manager());
+        diff_report.package_anomalies = package_inspector.inspect(&diff_report);
+    }
+
+    if cli.size || cli.size_threshold.is_some() {
+        let size_comparator =
+            SizeComparator::new(diff_builder.registry_manager(), &config, cli.size_threshold);
+        diff_report.size_report = Some(size_comparator.compare(&diff_report));
+    }
+
+    let forge_links = ForgeLinks::new(config.forges.clone());
     if cli.verbose {
-        VerboseReportPrinter::new(cli.group, cli.diff_rs).print(&diff_report);
+        VerboseReportPrinter::new(cli.group, cli.diff_rs, forge_links).print(&diff_report);
     } else {
-        SimpleReportPrinter::new(cli.group, cli.diff_rs).print(&diff_report);
+        SimpleReportPrinter::new(cli.group, cli.diff_rs, forge_links).print(&diff_report);
     }
 
-    Ok(())
-}
+    if !diff_report.checksum_mismatches.is_empty() {
+        let mismatched: BTreeSet<_> = diff_report
+            .checksum_mismatches
+            .iter()
+            .map(|m| format!("{}@{}", m.name, m.version))
+            .collect();
+        return Err(anyhow!(
+            "Checksums of the following crates don't match or are missing: {}",
+            mismatched.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
 
-fn compare_diffs(a: &CrateDiffInfo, b: &CrateDiffInfo) -> Ordering {
-    a.name
-        .cmp(&b.name)
-        .then_with(|| a.from_version.cmp(&b.from_version))
-        .then_with(|| a.to_version.cmp(&b.to_version))
+    let not_allowed: Vec<_> = diff_report
+        .license_diffs
+        .iter()
+        .filter(|d| !d.allowed)
+        .map(|d| format!("{}@{}", d.name, d.to_version))
+        .collect();
+    if !not_allowed.is_empty() {
+        return Err(anyhow!(
+            "Licenses of the following crates are not allowed: {}",
+            not_allowed.join(", ")
+        ));
+    }
+
+    Ok(())
 }
@@ -1,10 +1,11 @@
 use cargo_metadata::PackageId;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 
 use crate::package_source::PackageSource;
 
 /// Parsed PackageId information returned by cargo metadata command
-#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct PackageIdInfo {
     /// Package source: registry, path or other
     pub source: PackageSource,
@@ -14,6 +15,8 @@ pub struct PackageIdInfo {
     pub name: String,
     /// Crate version
     pub version: Version,
+    /// Commit of the git package
+    pub commit: Option<String>,
 }
 
 impl PackageIdInfo {
@@ -57,6 +60,7 @@ impl PackageIdInfo {
             path: path.into(),
             name: name.into(),
             version,
+            commit: None,
         })
     }
 
@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use semver::Version;
+
+use crate::crate_files::list_crate_files;
+use crate::diff_report::DiffReport;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// Hidden files and folders that are common in the published crates
+const STANDARD_HIDDEN_NAMES: [&str; 16] = [
+    ".cargo_vcs_info.json",
+    ".cargo-checksum.json",
+    ".clippy.toml",
+    ".codecov.yml",
+    ".editorconfig",
+    ".gitattributes",
+    ".github",
+    ".gitignore",
+    ".gitlab-ci.yml",
+    ".gitmodules",
+    ".mailmap",
+    ".pre-commit-config.yaml",
+    ".rustfmt.toml",
+    ".taplo.toml",
+    ".travis.yml",
+    ".vscode",
+];
+
+/// Maximum number of the path components before the path is considered suspiciously deep
+const MAX_PATH_DEPTH: usize = 12;
+
+/// Maximum path length before the path is considered suspiciously long
+const MAX_PATH_LEN: usize = 200;
+
+/// Unicode characters that change text direction and can hide the real file name
+const BIDI_CHARS: [char; 9] = [
+    '\u{202A}', '\u{202B}', '\u{202C}', '\u{202D}', '\u{202E}', '\u{2066}', '\u{2067}', '\u{2068}',
+    '\u{2069}',
+];
+
+/// Structural red flag in the crate package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    /// Symbolic link
+    Symlink(String),
+    /// Non-standard hidden file or folder
+    HiddenFile(String),
+    /// File name with path traversal, control or text direction characters
+    SuspiciousName(String),
+    /// Extremely deep or long path
+    DeepPath(String),
+    /// File names that differ only in case and collide on case-insensitive file systems
+    CaseCollision(Vec<String>),
+}
+
+impl Anomaly {
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Symlink(_) => "symlink",
+            Self::HiddenFile(_) => "hidden file",
+            Self::SuspiciousName(_) => "suspicious name",
+            Self::DeepPath(_) => "deep path",
+            Self::CaseCollision(_) => "case collision",
+        }
+    }
+
+    /// Affected path(s). Non-printable characters are escaped
+    pub fn path(&self) -> String {
+        match self {
+            Self::Symlink(path)
+            | Self::HiddenFile(path)
+            | Self::SuspiciousName(path)
+            | Self::DeepPath(path) => path.escape_debug().to_string(),
+            Self::CaseCollision(paths) => paths
+                .iter()
+                .map(|p| p.escape_debug().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+/// Structural red flags of the crate version package
+#[derive(Debug, Clone)]
+pub struct PackageAnomalies {
+    /// Crate name
+    pub name: String,
+    /// Inspected crate version
+    pub version: Version,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Inspects unpacked crate packages for the structural red flags
+pub struct PackageInspector<'a> {
+    registry_manager: &'a RegistryManager,
+}
+
+impl<'a> PackageInspector<'a> {
+    pub fn new(registry_manager: &'a RegistryManager) -> Self {
+        Self { registry_manager }
+    }
+
+    /// Inspects target versions of all updated and added crates in the report
+    pub fn inspect(&self, report: &DiffReport) -> Vec<PackageAnomalies> {
+        let mut package_anomalies = vec![];
+
+        for diff in report.crate_diffs() {
+            let Some(to_version) = &diff.to_version else {
+                continue;
+            };
+            let Some(crate_path) = self.registry_manager.fetch_crate(
+                &diff.name,
+                to_version,
+                diff.to_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+
+            let git_checkout = matches!(diff.registry, RegistrySource::Git(_));
+            match Self::inspect_package(&crate_path, git_checkout) {
+                Ok(anomalies) => {
+                    if !anomalies.is_empty() {
+                        package_anomalies.push(PackageAnomalies {
+                            name: diff.name.clone(),
+                            version: to_version.clone(),
+                            anomalies,
+                        });
+                    }
+                }
+                Err(err) => {
+                    eprintln!(
+                        "[ERROR] Cannot inspect '{}@{to_version}' crate package. Error: {err:#}",
+                        diff.name
+                    );
+                }
+            }
+        }
+
+        package_anomalies
+    }
+
+    fn inspect_package(crate_path: &Path, git_checkout: bool) -> anyhow::Result<Vec<Anomaly>> {
+        let files = list_crate_files(crate_path, git_checkout)?;
+        let mut anomalies = vec![];
+        // all files and folders to detect case collisions
+        let mut all_paths = BTreeSet::new();
+
+        for path in files {
+            if std::fs::symlink_metadata(crate_path.join(&path))?.is_symlink() {
+                anomalies.push(Anomaly::Symlink(path.clone()));
+            }
+
+            let components: Vec<_> = path.split('/').collect();
+            if components
+                .iter()
+                .any(|c| c.starts_with('.') && !STANDARD_HIDDEN_NAMES.contains(c))
+            {
+                anomalies.push(Anomaly::HiddenFile(path.clone()));
+            }
+            if components.iter().any(|c| is_suspicious_name(c)) {
+                anomalies.push(Anomaly::SuspiciousName(path.clone()));
+            }
+            if components.len() > MAX_PATH_DEPTH || path.len() > MAX_PATH_LEN {
+                anomalies.push(Anomaly::DeepPath(path.clone()));
+            }
+
+            for (i, _) in path.match_indices('/') {
+                all_paths.insert(path[..i].to_string());
+            }
+            all_paths.insert(path);
+        }
+
+        let mut lowercase_paths: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for path in all_paths {
+            lowercase_paths
+                .entry(path.to_lowercase())
+                .or_default()
+                .push(path);
+        }
+
+        anomalies.extend(
+            lowercase_paths
+                .into_values()
+                .filter(|paths| paths.len() > 1)
+                .map(Anomaly::CaseCollision),
+        );
+
+        Ok(anomalies)
+    }
+}
+
+fn is_suspicious_name(name: &str) -> bool {
+    name == ".."
+        || name.contains('\\')
+        || name.contains(':')
+        || name
+            .chars()
+            .any(|c| c.is_control() || BIDI_CHARS.contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::package_inspector::{Anomaly, PackageInspector, is_suspicious_name};
+
+    #[test]
+    fn test_suspicious_names() {
+        assert!(!is_suspicious_name("lib.rs"));
+        assert!(!is_suspicious_name("..rs"));
+        assert!(is_suspicious_name(".."));
+        assert!(is_suspicious_name("..\\..\\evil"));
+        assert!(is_suspicious_name("C:evil"));
+        assert!(is_suspicious_name("new\nline.rs"));
+        assert!(is_suspicious_name("lib\u{202E}sr.exe"));
+    }
+
+    #[test]
+    fn test_inspect_package() {
+        let crate_dir = tempfile::tempdir().unwrap();
+        let crate_path = crate_dir.path();
+        let nested_path = crate_path.join("src/a/b/c/d/e/f/g/h/i/j/k");
+        std::fs::create_dir_all(&nested_path).unwrap();
+        std::fs::create_dir_all(crate_path.join(".github")).unwrap();
+        for file in [
+            "Cargo.toml",
+            "src/lib.rs",
+            "src/Lib.rs",
+            ".secret",
+            ".gitignore",
+            ".github/ci.yml",
+            "src/a/b/c/d/e/f/g/h/i/j/k/deep.rs",
+        ] {
+            std::fs::write(crate_path.join(file), "").unwrap();
+        }
+
+        let anomalies = PackageInspector::inspect_package(crate_path, false);
+
+        assert_eq!(
+            anomalies.unwrap(),
+            [
+                Anomaly::HiddenFile(".secret".into()),
+                Anomaly::DeepPath("src/a/b/c/d/e/f/g/h/i/j/k/deep.rs".into()),
+                Anomaly::CaseCollision(vec!["src/Lib.rs".into(), "src/lib.rs".into()]),
+            ]
+        );
+    }
+}
@@ -1,8 +1,12 @@
+use serde::{Deserialize, Serialize};
+
 /// Crate source from the cargo metadata PackageId
-#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum PackageSource {
     /// Crate from the registry
     Registry,
+    /// Crate from the sparse registry
+    Sparse,
     /// Local crate, i.e. workspace member
     Path,
     /// Crate from the Git repository
@@ -22,6 +26,7 @@ impl PackageSource {
     pub fn as_str(&self) -> &str {
         match self {
             Self::Registry => "registry",
+            Self::Sparse => "sparse",
             Self::Path => "path",
             Self::Git => "git",
             Self::Unsupported(source) => source,
@@ -33,6 +38,7 @@ impl From<&str> for PackageSource {
     fn from(value: &str) -> Self {
         match value {
             "registry" => Self::Registry,
+            "sparse" => Self::Sparse,
             "path" => Self::Path,
             "git" => Self::Git,
             _ => Self::Unsupported(value.into()),
@@ -0,0 +1,124 @@
+use serde::Deserialize;
+
+/// Include/exclude glob filters for the crate source file paths.
+///
+/// Supported wildcards:
+/// - `*` - any sequence of characters except `/`
+/// - `?` - any single character except `/`
+/// - `**` - any sequence of characters including `/`
+///
+/// Patterns without `/` are matched against the file name only, e.g. `*.snap`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PathFilter {
+    /// Patterns of the files to keep. Empty list keeps all files
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Patterns of the files to ignore
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl PathFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        Self { include, exclude }
+    }
+
+    /// Adds patterns of the other filter to this one
+    pub fn merge(&mut self, other: &PathFilter) {
+        self.include.extend(other.include.iter().cloned());
+        self.exclude.extend(other.exclude.iter().cloned());
+    }
+
+    /// Checks if relative path (with `/` separators) passes the filter
+    pub fn matches(&self, path: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| Self::match_pattern(p, path)))
+            && !self.exclude.iter().any(|p| Self::match_pattern(p, path))
+    }
+
+    fn match_pattern(pattern: &str, path: &str) -> bool {
+        if pattern.contains('/') {
+            glob_match(pattern.as_bytes(), path.as_bytes())
+        } else {
+            let file_name = path.rsplit_once('/').map(|(_, name)| name).unwrap_or(path);
+            glob_match(pattern.as_bytes(), file_name.as_bytes())
+        }
+    }
+}
+
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((b'*', rest)) if rest.first() == Some(&b'*') => {
+            let rest = &rest[1..];
+            // '**/' also matches zero folders
+            if let Some(rest) = rest.strip_prefix(b"/")
+                && glob_match(rest, path)
+            {
+                return true;
+            }
+            (0..=path.len()).any(|i| glob_match(rest, &path[i..]))
+        }
+        Some((b'*', rest)) => (0..=path.len())
+            .take_while(|&i| i == 0 || path[i - 1] != b'/')
+            .any(|i| glob_match(rest, &path[i..])),
+        Some((b'?', rest)) => {
+            path.first().is_some_and(|&c| c != b'/') && glob_match(rest, &path[1..])
+        }
+        Some((c, rest)) => path.first() == Some(c) && glob_match(rest, &path[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path_filter::PathFilter;
+
+    #[test]
+    fn test_empty_filter() {
+        let filter = PathFilter::default();
+        assert!(filter.matches("src/lib.rs"));
+        assert!(filter.matches("tests/data/file.snap"));
+    }
+
+    #[test]
+    fn test_exclude() {
+        let filter = PathFilter::new(
+            vec![],
+            vec!["tests/**".into(), "benches/**".into(), "*.snap".into()],
+        );
+        assert!(filter.matches("src/lib.rs"));
+        assert!(filter.matches("build.rs"));
+        assert!(!filter.matches("tests/integration.rs"));
+        assert!(!filter.matches("tests/data/nested/file.txt"));
+        assert!(!filter.matches("benches/bench.rs"));
+        assert!(!filter.matches("src/snapshots/lib.snap"));
+        assert!(filter.matches("src/tests/mod.rs"));
+    }
+
+    #[test]
+    fn test_include() {
+        let filter = PathFilter::new(vec!["src/**".into(), "build.rs".into()], vec![]);
+        assert!(filter.matches("src/lib.rs"));
+        assert!(filter.matches("src/nested/mod.rs"));
+        assert!(filter.matches("build.rs"));
+        assert!(!filter.matches("Cargo.toml"));
+        assert!(!filter.matches("tests/test.rs"));
+    }
+
+    #[test]
+    fn test_include_and_exclude() {
+        let filter = PathFilter::new(vec!["src/**".into()], vec!["src/**/generated/*.rs".into()]);
+        assert!(filter.matches("src/lib.rs"));
+        assert!(!filter.matches("src/generated/bindings.rs"));
+        assert!(!filter.matches("src/a/b/generated/bindings.rs"));
+        assert!(filter.matches("src/generated/nested/bindings.rs"));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let filter = PathFilter::new(vec!["src/*.rs".into(), "file?.txt".into()], vec![]);
+        assert!(filter.matches("src/lib.rs"));
+        assert!(!filter.matches("src/nested/mod.rs"));
+        assert!(filter.matches("data/file1.txt"));
+        assert!(!filter.matches("data/file10.txt"));
+    }
+}
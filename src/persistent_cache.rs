@@ -0,0 +1,192 @@
+use std::hash::Hasher;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::cargo_config::cargo_home;
+use crate::stable_hasher::StableHasher;
+
+/// How long the entries that depend on the latest published versions stay valid
+pub const LATEST_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Kind of the cached values. Each kind is stored in its own folder
+#[derive(Debug, Clone, Copy)]
+pub enum CacheKind {
+    /// Crate version and repository
+    CrateInfo,
+    /// Commit hash and crate path from the `.cargo_vcs_info.json`
+    VcsInfo,
+    /// Resolved nested packages of the crate version
+    NestedPackages,
+}
+
+impl CacheKind {
+    fn dir_name(&self) -> &str {
+        match self {
+            Self::CrateInfo => "crate-info",
+            Self::VcsInfo => "vcs-info",
+            Self::NestedPackages => "nested-packages",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<T> {
+    /// Full key to detect the file name hash collisions
+    key: String,
+    /// Creation time in seconds since the UNIX epoch
+    created: u64,
+    value: T,
+}
+
+/// On-disk cache shared between the runs. Every entry is stored in its own JSON file
+#[derive(Debug, Default)]
+pub struct PersistentCache {
+    /// Cache folder. `None` disables the cache
+    cache_path: Option<PathBuf>,
+    /// Ignore the stored entries and overwrite them
+    refresh: bool,
+}
+
+impl PersistentCache {
+    pub fn new(cache_path: PathBuf, refresh: bool) -> Self {
+        Self {
+            cache_path: Some(cache_path),
+            refresh,
+        }
+    }
+
+    /// Default cache folder: `$XDG_CACHE_HOME/cargo-ddd` or `$CARGO_HOME/cargo-ddd`
+    pub fn default_path() -> Result<PathBuf> {
+        match std::env::var_os("XDG_CACHE_HOME") {
+            Some(cache_home) if !cache_home.is_empty() => {
+                Ok(PathBuf::from(cache_home).join("cargo-ddd"))
+            }
+            _ => Ok(cargo_home()?.join("cargo-ddd")),
+        }
+    }
+
+    /// Cached value. Entries older than `ttl` are ignored. Entries without `ttl` never expire
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        kind: CacheKind,
+        key: &str,
+        ttl: Option<Duration>,
+    ) -> Option<T> {
+        if self.refresh {
+            return None;
+        }
+        let data = std::fs::read(self.entry_path(kind, key)?).ok()?;
+        let entry: CacheEntry<T> = serde_json::from_slice(&data).ok()?;
+        if entry.key != key {
+            return None;
+        }
+        if let Some(ttl) = ttl
+            && now().saturating_sub(entry.created) >= ttl.as_secs()
+        {
+            return None;
+        }
+        Some(entry.value)
+    }
+
+    pub fn put<T: Serialize>(&self, kind: CacheKind, key: &str, value: &T) {
+        let Some(entry_path) = self.entry_path(kind, key) else {
+            return;
+        };
+        let entry = CacheEntry {
+            key: key.to_string(),
+            created: now(),
+            value,
+        };
+        let result = serde_json::to_vec(&entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| {
+                let dir = entry_path.parent().unwrap_or(&entry_path);
+                std::fs::create_dir_all(dir)?;
+                // write into the temporary file first so the concurrent runs never read a partial entry
+                let mut file = tempfile::NamedTempFile::new_in(dir)?;
+                file.write_all(&data)?;
+                file.persist(&entry_path)?;
+                Ok(())
+            });
+        if let Err(err) = result {
+            eprintln!("[WARN] Cannot write cache entry {entry_path:?}. Error: {err:#}");
+        }
+    }
+
+    fn entry_path(&self, kind: CacheKind, key: &str) -> Option<PathBuf> {
+        let mut hasher = StableHasher::new();
+        hasher.write(key.as_bytes());
+        let hash = hasher.finish();
+        Some(
+            self.cache_path
+                .as_ref()?
+                .join(kind.dir_name())
+                .join(format!("{hash:016x}.json")),
+        )
+    }
+}
+
+/// Seconds since the UNIX epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::persistent_cache::{CacheKind, PersistentCache};
+
+    #[test]
+    fn test_persistent_cache() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let cache = PersistentCache::new(cache_dir.path().into(), false);
+        cache.put(CacheKind::VcsInfo, "crates.io|foo@1.0.0", &"abc");
+
+        assert_eq!(
+            Some("abc".to_string()),
+            cache.get(CacheKind::VcsInfo, "crates.io|foo@1.0.0", None)
+        );
+        assert_eq!(
+            Some("abc".to_string()),
+            cache.get(
+                CacheKind::VcsInfo,
+                "crates.io|foo@1.0.0",
+                Some(Duration::from_secs(60))
+            )
+        );
+        assert_eq!(
+            None::<String>,
+            cache.get(
+                CacheKind::VcsInfo,
+                "crates.io|foo@1.0.0",
+                Some(Duration::ZERO)
+            )
+        );
+        assert_eq!(
+            None::<String>,
+            cache.get(CacheKind::CrateInfo, "crates.io|foo@1.0.0", None)
+        );
+
+        let refresh_cache = PersistentCache::new(cache_dir.path().into(), true);
+        assert_eq!(
+            None::<String>,
+            refresh_cache.get(CacheKind::VcsInfo, "crates.io|foo@1.0.0", None)
+        );
+
+        let disabled_cache = PersistentCache::default();
+        disabled_cache.put(CacheKind::VcsInfo, "crates.io|bar@1.0.0", &"abc");
+        assert_eq!(
+            None::<String>,
+            disabled_cache.get(CacheKind::VcsInfo, "crates.io|bar@1.0.0", None)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use semver::Version;
+
+use crate::crate_manifest::CrateManifest;
+use crate::repository_url::{RepositoryUrl, normalize_repository};
+
+/// Published crate version
+#[derive(Debug, Clone)]
+pub struct VersionInfo {
+    pub version: Version,
+    pub yanked: bool,
+    /// SHA-256 checksum of the `.crate` archive
+    pub checksum: Option<String>,
+}
+
+/// Metadata of the crate version
+#[derive(Debug, Clone, Default)]
+pub struct CrateMetadata {
+    /// Repository URL
+    pub repository: Option<String>,
+    /// Homepage URL
+    pub homepage: Option<String>,
+}
+
+impl CrateMetadata {
+    /// Reads metadata from the manifest of the unpacked crate
+    pub fn read(crate_path: &Path) -> Result<Self> {
+        let package = CrateManifest::read(crate_path)?.package;
+        Ok(Self {
+            repository: package.repository,
+            homepage: package.homepage,
+        })
+    }
+
+    /// Normalized repository URL. GitHub homepage is used if the repository is not specified
+    pub fn repository_url(&self) -> Option<String> {
+        self.repository
+            .as_deref()
+            .map(normalize_repository)
+            .or_else(|| github_repository(self.homepage.as_deref()?))
+    }
+
+    /// Crate folder from the `tree`/`blob` repository URL
+    pub fn repository_subpath(&self) -> Option<String> {
+        RepositoryUrl::parse(self.repository.as_deref()?).subpath
+    }
+}
+
+/// Source of the published crates information and sources
+pub trait RegistryBackend {
+    /// Published versions of the crate sorted by version
+    fn list_versions(&self, crate_name: &str) -> Result<Vec<VersionInfo>>;
+
+    /// Updates locally cached versions of the crate. The latest version may be missing otherwise
+    fn refresh(&self, _crate_name: &str) {}
+
+    fn crate_metadata(&self, crate_name: &str, version: &Version) -> Result<CrateMetadata>;
+
+    /// Path to the crate sources if they are already available locally
+    fn source_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf>;
+
+    /// Downloads the crate sources and returns path to them
+    fn download_source(&self, crate_name: &str, version: &Version) -> Result<PathBuf>;
+
+    /// Path to the `.crate` archive. Downloads it if it's not available locally
+    fn download_archive(&self, crate_name: &str, version: &Version) -> Result<PathBuf>;
+}
+
+/// The highest version that is not yanked. Pre-release versions are skipped unless `pre` is set
+pub fn latest_version(versions: &[VersionInfo], pre: bool) -> Option<Version> {
+    versions
+        .iter()
+        .filter(|v| !v.yanked && (pre || v.version.pre.is_empty()))
+        .map(|v| v.version.clone())
+        .max()
+}
+
+/// GitHub repository URL from the GitHub homepage URL
+fn github_repository(homepage: &str) -> Option<String> {
+    let url = normalize_repository(homepage);
+    let path = url.strip_prefix("https://github.com/")?;
+    (path.split('/').count() == 2).then_some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::registry_backend::{VersionInfo, latest_version};
+
+    #[test]
+    fn test_latest_version() {
+        let versions: Vec<_> = [("1.0.0", false), ("1.1.0", true), ("1.2.0-rc.1", false)]
+            .into_iter()
+            .map(|(version, yanked)| VersionInfo {
+                version: Version::parse(version).unwrap(),
+                yanked,
+                checksum: None,
+            })
+            .collect();
+
+        assert_eq!(
+            Some(Version::new(1, 0, 0)),
+            latest_version(&versions, false)
+        );
+        assert_eq!(
+            Some(Version::parse("1.2.0-rc.1").unwrap()),
+            latest_version(&versions, true)
+        );
+        assert_eq!(None, latest_version(&versions[1..2], false));
+    }
+}
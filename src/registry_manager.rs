@@ -1,111 +1,740 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use anyhow::{Result, anyhow};
 use semver::Version;
+use tempfile::TempDir;
 
-use crate::cargo_runner::CargoRunner;
+use crate::cargo_cache_backend::{CargoCacheBackend, registry_dir_name};
+use crate::cargo_config::{CRATES_IO_SOURCE, CargoConfig, ReplacementSource, cargo_home};
+use crate::cargo_lock::CargoLock;
+use crate::crate_archive::{
+    ChecksumMismatch, ChecksumSource, sha256_file, unpack_crate, unpacked_crate,
+};
 use crate::crate_info::CrateInfo;
+use crate::crate_prefetch::{
+    LockedPackage, PrefetchCrate, fetch_layer, semver_layers, update_index,
+};
+use crate::crates_io_backend::CratesIoBackend;
+use crate::directory_backend::DirectoryBackend;
+use crate::git_checkouts::{GitCheckouts, find_package};
+use crate::git_source::GitSource;
+use crate::local_registry_backend::LocalRegistryBackend;
 use crate::package_id_info::PackageIdInfo;
 use crate::package_source::PackageSource;
+use crate::persistent_cache::{CacheKind, LATEST_TTL, PersistentCache};
+use crate::registry_backend::{CrateMetadata, RegistryBackend, VersionInfo, latest_version};
+use crate::registry_source::{CRATES_IO_INDEX, CRATES_IO_SPARSE_INDEX, RegistrySource};
+use crate::vcs_info::{VCS_INFO_FILE_NAME, VcsInfo};
+use crate::vendor_dir::VendorDir;
+
+/// Where the crate sources the cached values are computed from are taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrateSource {
+    /// Checkout of the git repository
+    Git,
+    /// Vendor directory or directory source. Vendored crates may be patched
+    Vendored,
+    /// Unpacked from the verified `.crate` archive
+    Verified,
+    /// Local cargo registry or other registry backend
+    Registry,
+}
+
+impl CrateSource {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Git => "git",
+            Self::Vendored => "vendored",
+            Self::Verified => "verified",
+            Self::Registry => "registry",
+        }
+    }
+
+    /// Persistent cache key of the value computed from the crate sources. Values computed from the verified sources
+    /// are never cached, so the unverified sources of the earlier runs are never trusted
+    pub fn cache_key(&self, key: &str) -> Option<String> {
+        (*self != Self::Verified).then(|| format!("{}|{key}", self.as_str()))
+    }
+}
 
 /// Manager for the local cargo registry crate sorces
 pub struct RegistryManager {
-    /// Path to the current user cargo registry source folder
+    /// Path to the current user cargo registry root folder: `$CARGO_HOME/registry`
     registry_path: PathBuf,
+    /// Cargo configuration
+    cargo_config: CargoConfig,
     /// Crates info cache
-    crate_info_cache: HashMap<String, CrateInfo>,
+    crate_info_cache: Mutex<HashMap<(RegistrySource, String), CrateInfo>>,
+    /// Yanked versions of the crates. Yanked state may change at any time so it's never stored on disk
+    yanked_cache: Mutex<HashMap<(RegistrySource, String), Vec<Version>>>,
+    /// Crates whose index cache entries are already updated during this run
+    refreshed: Mutex<HashSet<(RegistrySource, String)>>,
+    /// On-disk cache shared between the runs
+    persistent_cache: PersistentCache,
+    /// Vendor directories that are checked before the local registry
+    vendor_dirs: Vec<VendorDir>,
+    /// Cargo git dependencies cache
+    git_checkouts: GitCheckouts,
+    /// crates.io HTTP API backend that is used instead of the local cargo cache for crates.io crates
+    crates_io_api: Option<CratesIoBackend>,
+    /// Private folder the verified crate archives are unpacked to. Set if the checksums are verified
+    verified_crates_dir: Option<TempDir>,
+    /// Checksums of the `.crate` archives recorded in the `Cargo.lock`
+    cargo_lock: CargoLock,
+    /// Pre-release versions may be picked as the latest ones
+    pre: bool,
+    /// Use only the local registry and index cache
+    offline: bool,
 }
 
 impl RegistryManager {
-    pub fn new(registry_path: Option<PathBuf>) -> Result<Self> {
-        if let Some(registry_path) = registry_path {
-            return Ok(Self {
-                registry_path,
-                crate_info_cache: HashMap::new(),
-            });
+    pub fn new(offline: bool) -> Result<Self> {
+        let cargo_home_path = cargo_home()?;
+
+        Ok(Self {
+            registry_path: cargo_home_path.join("registry"),
+            cargo_config: CargoConfig::load(&cargo_home_path),
+            crate_info_cache: Mutex::new(HashMap::new()),
+            yanked_cache: Mutex::new(HashMap::new()),
+            refreshed: Mutex::new(HashSet::new()),
+            persistent_cache: PersistentCache::default(),
+            vendor_dirs: vec![],
+            git_checkouts: GitCheckouts::new(&cargo_home_path, offline),
+            crates_io_api: None,
+            verified_crates_dir: None,
+            cargo_lock: CargoLock::default(),
+            pre: false,
+            offline,
+        })
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// On-disk cache of the crates info, commit hashes and nested packages
+    pub fn persistent_cache(&self) -> &PersistentCache {
+        &self.persistent_cache
+    }
+
+    pub fn use_persistent_cache(&mut self, persistent_cache: PersistentCache) {
+        self.persistent_cache = persistent_cache;
+    }
+
+    /// Vendor directory from the cargo source replacement configuration
+    pub fn detect_vendor_dir(&self) -> Option<PathBuf> {
+        self.cargo_config.vendor_dir()
+    }
+
+    /// Crates of the vendor directory are used instead of the local registry ones
+    pub fn add_vendor_dir(&mut self, vendor_dir: VendorDir) {
+        self.vendor_dirs.push(vendor_dir);
+    }
+
+    /// Use crates.io compatible HTTP API with the base URL instead of the local cargo cache for crates.io crates
+    pub fn use_crates_io_api(&mut self, base_url: &str) {
+        self.crates_io_api = Some(CratesIoBackend::new(base_url));
+    }
+
+    /// Allow pre-release versions when the latest crate version is picked
+    pub fn allow_pre_releases(&mut self) {
+        self.pre = true;
+    }
+
+    /// Verify checksums of the `.crate` archives against the `Cargo.lock` and the registry index.
+    /// Registry crate sources are unpacked from the verified archives into a private temporary folder.
+    pub fn verify_checksums(&mut self, cargo_lock: CargoLock) -> Result<()> {
+        self.verified_crates_dir = Some(tempfile::Builder::new().prefix("cargo-ddd-").tempdir()?);
+        self.cargo_lock = cargo_lock;
+        Ok(())
+    }
+
+    /// Index URL of the registry
+    pub fn registry_index(&self, registry: &RegistrySource) -> Option<String> {
+        match registry {
+            RegistrySource::CratesIo => {
+                let protocol = std::env::var("CARGO_REGISTRIES_CRATES_IO_PROTOCOL");
+                if protocol.as_deref() == Ok("git") {
+                    Some(CRATES_IO_INDEX.into())
+                } else {
+                    Some(CRATES_IO_SPARSE_INDEX.into())
+                }
+            }
+            RegistrySource::Named(name) => {
+                let index = self.cargo_config.registry_index(name);
+                if index.is_none() {
+                    eprintln!("[ERROR] Registry '{name}' is not found in the cargo configuration");
+                }
+                index
+            }
+            RegistrySource::Index(index) => Some(index.clone()),
+            RegistrySource::Git(_) => None,
+        }
+    }
+
+    /// Source that replaces the registry in the cargo configuration
+    fn replacement(&self, registry: &RegistrySource) -> Option<ReplacementSource> {
+        match registry {
+            RegistrySource::CratesIo => self.cargo_config.replacement(CRATES_IO_SOURCE),
+            RegistrySource::Named(name) => self.cargo_config.replacement(name),
+            RegistrySource::Index(_) | RegistrySource::Git(_) => None,
+        }
+    }
+
+    /// Crate is taken from the vendor directory or the directory source. There is no archive to verify
+    fn vendored(&self, crate_name: &str, version: &Version, registry: &RegistrySource) -> bool {
+        self.get_vendor_crate_path(crate_name, version).is_some()
+            || matches!(
+                self.replacement(registry),
+                Some(ReplacementSource::Directory(_))
+            )
+    }
+
+    /// Kind of the sources `fetch_crate` returns for the crate
+    pub fn crate_source(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> CrateSource {
+        if let RegistrySource::Git(_) = registry {
+            CrateSource::Git
+        } else if self.vendored(crate_name, version, registry) {
+            CrateSource::Vendored
+        } else if self.verified_crates_dir.is_some() {
+            CrateSource::Verified
+        } else {
+            CrateSource::Registry
         }
+    }
+
+    /// Registry is replaced with a local registry or a directory that is available without the network
+    fn local_source(&self, registry: &RegistrySource) -> bool {
+        matches!(
+            self.replacement(registry),
+            Some(ReplacementSource::LocalRegistry(_) | ReplacementSource::Directory(_))
+        )
+    }
 
-        // Try to find registry path
-        let cargo_home = match std::env::var("CARGO_HOME") {
-            Ok(cargo_home) => cargo_home,
-            Err(_) => format!("{}/.cargo", std::env::var("HOME")?),
+    /// Registry crates are kept in the local cargo cache and can be downloaded or updated with cargo in batches.
+    /// crates.io API backend, git crates and local sources are not in the local cargo cache
+    fn cargo_cache(&self, registry: &RegistrySource) -> bool {
+        let cargo_cache = match registry {
+            RegistrySource::Git(_) => false,
+            RegistrySource::CratesIo => self.crates_io_api.is_none(),
+            _ => true,
         };
+        cargo_cache && !self.local_source(registry)
+    }
 
-        let cargo_home_path = PathBuf::from(&cargo_home);
+    /// Backend of the registry. crates.io API is used for crates.io if it's configured.
+    /// Otherwise the source replacement of the cargo configuration is honored
+    fn backend(&self, registry: &RegistrySource) -> Option<Box<dyn RegistryBackend>> {
+        if *registry == RegistrySource::CratesIo
+            && let Some(crates_io_api) = &self.crates_io_api
+        {
+            return Some(Box::new(crates_io_api.clone()));
+        }
 
-        if !std::fs::exists(&cargo_home_path)? {
-            return Err(anyhow!("Cargo path doesn't exist: {cargo_home}"));
+        match self.replacement(registry) {
+            // cargo keeps the mirror crates in the local cache under the mirror index name
+            Some(ReplacementSource::Registry(index)) => {
+                return Some(Box::new(CargoCacheBackend::new(
+                    &self.registry_path,
+                    registry.clone(),
+                    &index,
+                    self.offline,
+                )));
+            }
+            Some(ReplacementSource::LocalRegistry(path)) => {
+                return Some(Box::new(LocalRegistryBackend::new(registry.clone(), &path)));
+            }
+            Some(ReplacementSource::Directory(path)) => {
+                return Some(Box::new(DirectoryBackend::new(registry.clone(), &path)));
+            }
+            None => {}
         }
 
-        // NOTE: this is a very 'hacky' way to get local cargo registry source folder
-        let registry_src_dir = cargo_home_path.join("registry").join("src");
-        let entries = std::fs::read_dir(registry_src_dir)?;
-        let mut buf = vec![];
+        let index = self.registry_index(registry)?;
+        Some(Box::new(CargoCacheBackend::new(
+            &self.registry_path,
+            registry.clone(),
+            &index,
+            self.offline,
+        )))
+    }
 
-        // Find registry folder name
-        for entry in entries {
-            let entry = entry?;
-            let meta = entry.metadata()?;
+    /// Get path to the crate source code in the vendor directories or in the local cargo registry if it's there
+    pub fn get_crate_path(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Option<PathBuf> {
+        if let Some(vendor_crate_path) = self.get_vendor_crate_path(crate_name, version) {
+            return Some(vendor_crate_path);
+        }
 
-            if meta.is_dir() {
-                let Ok(file_name) = entry.file_name().into_string() else {
-                    eprintln!("[ERROR] Cannot read file name: {:?}", entry.file_name());
-                    continue;
-                };
-                // By default we are using sources from the crates.io registry
-                if file_name.starts_with("index.crates.io-") {
-                    buf.push(entry.path());
-                }
+        self.backend(registry)?.source_path(crate_name, version)
+    }
+
+    fn get_vendor_crate_path(&self, crate_name: &str, version: &Version) -> Option<PathBuf> {
+        self.vendor_dirs
+            .iter()
+            .find_map(|vendor_dir| vendor_dir.find(crate_name, version))
+            .map(|vendor_crate| vendor_crate.path.clone())
+    }
+
+    /// Downloads the registry crates that are not available locally into the local registry in batches.
+    /// Yanked crates and crates that cannot be downloaded in a batch are fetched one by one when they are needed.
+    pub fn prefetch(&self, crates: impl IntoIterator<Item = PrefetchCrate>) {
+        if self.offline {
+            return;
+        }
+        let missing: BTreeSet<_> = crates
+            .into_iter()
+            .filter(|krate| {
+                self.cargo_cache(&krate.registry)
+                    && self
+                        .get_vendor_crate_path(&krate.name, &krate.version)
+                        .is_none()
+                    && self
+                        .get_crate_path(&krate.name, &krate.version, &krate.registry)
+                        .is_none()
+            })
+            .collect();
+        // cargo refuses to resolve a yanked version that is not locked, so it would fail the whole batch.
+        // Yanked crates are fetched in separate batches with the synthesized lockfile
+        let (yanked, missing): (BTreeSet<_>, BTreeSet<_>) = missing
+            .into_iter()
+            .partition(|krate| self.is_yanked(&krate.name, &krate.version, &krate.registry));
+        let yanked = yanked
+            .into_iter()
+            .filter_map(|mut krate| {
+                krate.locked = self.locked_package(&krate);
+                krate.locked.is_some().then_some(krate)
+            })
+            .collect();
+
+        for layer in semver_layers(missing)
+            .into_iter()
+            .chain(semver_layers(yanked))
+        {
+            if let Err(err) = fetch_layer(&layer) {
+                eprintln!(
+                    "[WARN] Cannot prefetch {} crates in one batch. They are fetched one by one. Error: {err:#}",
+                    layer.len()
+                );
             }
         }
+    }
+
+    /// Lockfile entry of the registry crate with the checksum from the registry index
+    fn locked_package(&self, krate: &PrefetchCrate) -> Option<LockedPackage> {
+        let index = self.registry_index(&krate.registry)?;
+        let source = match krate.registry {
+            // crates.io packages are always locked with the git index URL
+            RegistrySource::CratesIo => format!("registry+{CRATES_IO_INDEX}"),
+            _ if index.starts_with("sparse+") => index,
+            _ => format!(
+                "registry+{}",
+                index.strip_prefix("registry+").unwrap_or(&index)
+            ),
+        };
+        let checksum = self
+            .backend(&krate.registry)?
+            .list_versions(&krate.name)
+            .ok()?
+            .into_iter()
+            .find(|v| v.version == krate.version)?
+            .checksum?;
+        Some(LockedPackage { source, checksum })
+    }
+
+    /// Updates the index cache entries of the registry crates whose latest versions are not cached yet with a single cargo run.
+    /// Entries that cannot be updated in a batch are updated one by one when the latest versions are needed.
+    pub fn refresh_index(&self, crates: impl IntoIterator<Item = (String, RegistrySource)>) {
+        if self.offline {
+            return;
+        }
+        let stale: BTreeSet<_> = crates
+            .into_iter()
+            .filter(|(crate_name, registry)| {
+                self.cargo_cache(registry)
+                    && self.cached_latest_info(crate_name, registry).is_none()
+                    && !self.is_refreshed(crate_name, registry)
+            })
+            .collect();
+        if stale.is_empty() {
+            return;
+        }
+
+        match update_index(&stale) {
+            Ok(()) => self
+                .refreshed
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .extend(
+                    stale
+                        .into_iter()
+                        .map(|(crate_name, registry)| (registry, crate_name)),
+                ),
+            Err(err) => eprintln!(
+                "[WARN] Cannot update index cache of {} crates in one batch. They are updated one by one. Error: {err:#}",
+                stale.len()
+            ),
+        }
+    }
+
+    /// Latest version of the registry crate from the cached crate info or from the registry index.
+    /// Crate sources are not needed, so nothing is downloaded except the index entry.
+    /// Errors are reported when the crate info is requested
+    pub fn latest_version(&self, crate_name: &str, registry: &RegistrySource) -> Option<Version> {
+        if let RegistrySource::Git(_) = registry {
+            return None;
+        }
+        if let Some(info) = self.cached_latest_info(crate_name, registry) {
+            return info.version;
+        }
+
+        let backend = self.backend(registry)?;
+        self.refresh_crate(crate_name, registry, backend.as_ref());
+        let versions = backend.list_versions(crate_name).ok()?;
+        self.pick_latest(&versions).ok()
+    }
+
+    fn is_refreshed(&self, crate_name: &str, registry: &RegistrySource) -> bool {
+        self.refreshed
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .contains(&(registry.clone(), crate_name.to_string()))
+    }
+
+    /// Updates the crate index cache entry once per run. The latest version may be missing otherwise
+    fn refresh_crate(
+        &self,
+        crate_name: &str,
+        registry: &RegistrySource,
+        backend: &dyn RegistryBackend,
+    ) {
+        let inserted = self
+            .refreshed
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert((registry.clone(), crate_name.to_string()));
+        if inserted {
+            backend.refresh(crate_name);
+        }
+    }
+
+    /// Get path to the crate source code in the local cargo registry.
+    /// Downloads the crate into the local registry if it's not there yet.
+    /// Git crates are taken from the repository checkout of the commit `hash`.
+    pub fn fetch_crate(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        hash: Option<&str>,
+        registry: &RegistrySource,
+    ) -> Option<PathBuf> {
+        if let RegistrySource::Git(source) = registry {
+            let Some(commit) = hash else {
+                eprintln!("[WARN] Commit of the git crate '{crate_name}@{version}' is unknown");
+                return None;
+            };
+            return match self.fetch_git_crate(crate_name, source, commit) {
+                Ok((crate_path, _)) => Some(crate_path),
+                Err(err) => {
+                    eprintln!(
+                        "[ERROR] Cannot fetch '{crate_name}' crate sources of the commit {commit}. Error: {err:#}"
+                    );
+                    None
+                }
+            };
+        }
 
-        match buf.as_slice() {
-            [] => Err(anyhow!("Cannot get crates.io registry source code path")),
-            [first, remaider @ ..] => {
-                if !remaider.is_empty() {
+        if let Some(verified_crates_dir) = &self.verified_crates_dir
+            && !self.vendored(crate_name, version, registry)
+        {
+            return match self.fetch_verified_crate(
+                crate_name,
+                version,
+                registry,
+                verified_crates_dir.path(),
+            ) {
+                Ok(crate_path) => Some(crate_path),
+                Err(err) => {
                     eprintln!(
-                        "[WARN] There are {} registry sources. First will be used: {buf:#?}",
-                        buf.len()
+                        "[ERROR] Cannot fetch '{crate_name}@{version}' crate sources. Error: {err:#}"
                     );
+                    None
                 }
-                Ok(Self {
-                    registry_path: first.clone(),
-                    crate_info_cache: HashMap::new(),
-                })
+            };
+        }
+
+        if let Some(crate_path) = self.get_crate_path(crate_name, version, registry) {
+            return Some(crate_path);
+        }
+
+        if self.offline && !self.local_source(registry) {
+            eprintln!(
+                "[WARN] '{crate_name}@{version}' crate sources are not in the local registry and cannot be downloaded in offline mode"
+            );
+            return None;
+        }
+
+        match self.backend(registry)?.download_source(crate_name, version) {
+            Ok(crate_path) => Some(crate_path),
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot fetch '{crate_name}@{version}' crate sources. Error: {err:#}"
+                );
+                None
             }
         }
     }
 
-    /// Get path to the crate source code in the local cargo registry
-    pub fn get_crate_path(&self, crate_name: &str, version: &Version) -> PathBuf {
-        self.registry_path.join(format!("{crate_name}-{version}"))
+    /// Unpacks the crate archive into the private folder if its checksums match
+    fn fetch_verified_crate(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+        verified_crates_dir: &Path,
+    ) -> Result<PathBuf> {
+        let index = self
+            .registry_index(registry)
+            .ok_or_else(|| anyhow!("Unknown registry '{registry}'"))?;
+        let dest_path = verified_crates_dir.join(registry_dir_name(&index));
+        let crate_dir = format!("{crate_name}-{version}");
+        if let Some(crate_path) = unpacked_crate(&dest_path, &crate_dir) {
+            return Ok(crate_path);
+        }
+
+        let (archive_path, mismatches) = self.verify_archive(crate_name, version, registry)?;
+        if let Some(mismatch) = mismatches.first() {
+            return Err(match mismatch.source {
+                ChecksumSource::Missing => anyhow!(
+                    "There is no checksum to verify the crate archive {archive_path:?} against"
+                ),
+                source => anyhow!(
+                    "Checksum of the crate archive {archive_path:?} doesn't match the one from the {}",
+                    source.as_str()
+                ),
+            });
+        }
+        let archive = std::fs::File::open(&archive_path)?;
+        unpack_crate(archive, &dest_path, &crate_dir)
     }
 
-    /// Crate version commit hash from the '.cargo_vcs_info.json' file in the crate source folder in the loacl registry
+    /// Verifies checksum of the registry crate archive against the `Cargo.lock` and the registry index.
+    /// Vendored and git crates are not verified
+    pub fn verify_crate(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Result<Vec<ChecksumMismatch>> {
+        if matches!(registry, RegistrySource::Git(_))
+            || self.vendored(crate_name, version, registry)
+        {
+            return Ok(vec![]);
+        }
+        let (_, mismatches) = self.verify_archive(crate_name, version, registry)?;
+        Ok(mismatches)
+    }
+
+    /// Returns path to the crate archive and its checksum mismatches
+    fn verify_archive(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Result<(PathBuf, Vec<ChecksumMismatch>)> {
+        let backend = self
+            .backend(registry)
+            .ok_or_else(|| anyhow!("Unknown registry '{registry}'"))?;
+        let archive_path = backend.download_archive(crate_name, version)?;
+        let actual = sha256_file(&archive_path)?;
+
+        let mut expected = vec![];
+        if let Some(checksum) = self.cargo_lock.checksum(crate_name, version, registry) {
+            expected.push((ChecksumSource::Lockfile, checksum.to_string()));
+        }
+        match backend.list_versions(crate_name) {
+            Ok(versions) => {
+                if let Some(checksum) = versions
+                    .into_iter()
+                    .find(|v| v.version == *version)
+                    .and_then(|v| v.checksum)
+                {
+                    expected.push((ChecksumSource::Index, checksum));
+                }
+            }
+            Err(err) => {
+                eprintln!(
+                    "[WARN] Cannot get index checksum of the '{crate_name}@{version}' crate. Error: {err:#}"
+                );
+            }
+        }
+        if expected.is_empty() {
+            // unverified archive is never trusted
+            expected.push((ChecksumSource::Missing, String::new()));
+        }
+
+        let mismatches = expected
+            .into_iter()
+            .filter(|(source, checksum)| {
+                *source == ChecksumSource::Missing || !checksum.eq_ignore_ascii_case(&actual)
+            })
+            .map(|(source, checksum)| ChecksumMismatch {
+                name: crate_name.into(),
+                version: version.clone(),
+                source,
+                expected: checksum,
+                actual: actual.clone(),
+                archive: archive_path.clone(),
+            })
+            .collect();
+        Ok((archive_path, mismatches))
+    }
+
+    /// Checks the registry index whether the crate version is yanked.
+    /// Git crates and crates that are missing in the index are never yanked
+    pub fn is_yanked(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> bool {
+        if let RegistrySource::Git(_) = registry {
+            return false;
+        }
+
+        let cache_key = (registry.clone(), crate_name.to_string());
+        let cached = self
+            .yanked_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(&cache_key)
+            .map(|versions| versions.contains(version));
+        if let Some(yanked) = cached {
+            return yanked;
+        }
+
+        let yanked_versions: Vec<_> = self
+            .backend(registry)
+            .and_then(|backend| backend.list_versions(crate_name).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|v| v.yanked)
+            .map(|v| v.version)
+            .collect();
+        let yanked = yanked_versions.contains(version);
+        self.yanked_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(cache_key, yanked_versions);
+        yanked
+    }
+
+    /// Crate version commit hash from the '.cargo_vcs_info.json' file in the crate source folder in the loacl registry.
+    /// Git crates have the commit in their package source
     pub fn get_pkg_hash(&self, pkg_info: &PackageIdInfo) -> Option<String> {
+        self.get_pkg_vcs_info(pkg_info)
+            .map(|vcs_info| vcs_info.hash)
+    }
+
+    /// Version control information of the package. Git crates have only the commit from their package source
+    pub fn get_pkg_vcs_info(&self, pkg_info: &PackageIdInfo) -> Option<VcsInfo> {
         if pkg_info.source != PackageSource::Git {
-            self.get_crate_hash(&pkg_info.name, &pkg_info.version)
+            self.get_vcs_info(
+                &pkg_info.name,
+                &pkg_info.version,
+                &RegistrySource::from_package(pkg_info),
+            )
         } else {
-            eprintln!("[WARN] Cannot get hash for Git crate: {}", pkg_info.name);
-            None
+            if pkg_info.commit.is_none() {
+                eprintln!(
+                    "[WARN] Cannot get commit of the git crate: {}",
+                    pkg_info.name
+                );
+            }
+            Some(VcsInfo {
+                hash: pkg_info.commit.clone()?,
+                path_in_vcs: None,
+            })
+        }
+    }
+
+    /// Version and commit of the git crate at the newest commit of the tracked reference
+    pub fn get_git_latest(
+        &self,
+        crate_name: &str,
+        source: &GitSource,
+    ) -> Option<(Version, String)> {
+        let result = self.git_checkouts.latest_commit(source).and_then(|commit| {
+            let (_, version) = self.fetch_git_crate(crate_name, source, &commit)?;
+            Ok((version, commit))
+        });
+        match result {
+            Ok(latest) => Some(latest),
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot get the latest commit of the git crate '{crate_name}'. Error: {err:#}"
+                );
+                None
+            }
         }
     }
 
-    /// Crate version commit hash from the '.cargo_vcs_info.json' file in the crate source folder in the loacl registry
-    pub fn get_crate_hash(&self, crate_name: &str, version: &Version) -> Option<String> {
-        let cargo_runner = CargoRunner::new(None);
+    fn fetch_git_crate(
+        &self,
+        crate_name: &str,
+        source: &GitSource,
+        commit: &str,
+    ) -> Result<(PathBuf, Version)> {
+        let checkout = self.git_checkouts.checkout(source, commit)?;
+        find_package(&checkout, crate_name)
+    }
 
-        // Run 'cargo info' for the specific version of the crate to guarantee it's in the local registry
-        if let Err(err) = cargo_runner.run("info", [format!("{crate_name}@{version}")]) {
-            eprintln!("[ERROR] Cannot get '{crate_name}' crate info. Error: {err}");
-            return None;
+    /// Crate version commit hash and path from the '.cargo_vcs_info.json' file in the crate source folder in the loacl registry
+    pub fn get_vcs_info(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Option<VcsInfo> {
+        let cache_key = self
+            .crate_source(crate_name, version, registry)
+            .cache_key(&format!("{registry}|{crate_name}@{version}"));
+        if let Some(cache_key) = &cache_key
+            && let Some(vcs_info) = self
+                .persistent_cache
+                .get(CacheKind::VcsInfo, cache_key, None)
+        {
+            return Some(vcs_info);
         }
 
+        let vcs_info = self.read_vcs_info(crate_name, version, registry)?;
+        if let Some(cache_key) = &cache_key {
+            self.persistent_cache
+                .put(CacheKind::VcsInfo, cache_key, &vcs_info);
+        }
+        Some(vcs_info)
+    }
+
+    fn read_vcs_info(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+    ) -> Option<VcsInfo> {
         let vcs_info_path = self
-            .registry_path
-            .join(format!("{crate_name}-{version}"))
-            .join(".cargo_vcs_info.json");
+            .fetch_crate(crate_name, version, None, registry)?
+            .join(VCS_INFO_FILE_NAME);
 
         match std::fs::exists(&vcs_info_path) {
             Ok(file_exists) => {
@@ -125,8 +754,8 @@ impl RegistryManager {
             }
         }
 
-        let hash_data = match std::fs::read_to_string(&vcs_info_path) {
-            Ok(hash_data) => hash_data,
+        let vcs_info_data = match std::fs::read_to_string(&vcs_info_path) {
+            Ok(vcs_info_data) => vcs_info_data,
             Err(err) => {
                 // TODO: extract commit hash from the other sources
                 eprintln!(
@@ -136,124 +765,235 @@ impl RegistryManager {
             }
         };
 
-        // Do not parse Json. Just read hash from the line
-        if let Some(hash) = hash_data
-            .lines()
-            .find_map(|l| l.trim().strip_prefix("\"sha1\": \""))
-        {
-            // strip last " symbol
-            Some(hash[..hash.len() - 1].into())
-        } else {
-            // TODO: extract commit hash from the other sources
-            eprintln!("[WARN] Cannot get hash of the '{crate_name}' crate:\n{hash_data}");
-            None
+        match VcsInfo::parse(&vcs_info_data) {
+            Ok(vcs_info) => Some(vcs_info),
+            Err(err) => {
+                // TODO: extract commit hash from the other sources
+                eprintln!(
+                    "[WARN] Cannot get hash of the '{crate_name}' crate. Error: {err:#}\n{vcs_info_data}"
+                );
+                None
+            }
         }
     }
 
-    /// Extract crate version and repository from the output of the 'cargo info' command.
-    /// This will automatically download crate and its sources into the local cargo registry.
-    pub fn get_crate_info(&mut self, crate_name: &str, version: Option<&Version>) -> CrateInfo {
+    /// Get crate version and repository from the registry backend.
+    /// The local cargo cache backend downloads crate and its sources into the local cargo registry.
+    /// In offline mode only the local index cache and the local registry sources are used.
+    pub fn get_crate_info(
+        &self,
+        crate_name: &str,
+        version: Option<&Version>,
+        registry: &RegistrySource,
+    ) -> CrateInfo {
         let crate_desc = Self::get_crate_desc(crate_name, version);
+        let cache_key = (registry.clone(), crate_desc.clone());
+        if let Some(info) = self.cached_crate_info(&cache_key) {
+            return info;
+        }
 
-        self.crate_info_cache
-            .entry(crate_desc.clone())
-            .or_insert_with(|| Self::extract_crate_info(crate_name, crate_desc, version))
-            .clone()
-    }
+        if let RegistrySource::Git(source) = registry {
+            let version = match version {
+                Some(version) => Some(version.clone()),
+                None => self
+                    .get_git_latest(crate_name, source)
+                    .map(|(version, _)| version),
+            };
+            return CrateInfo {
+                version,
+                repository: Some(source.repository()),
+                homepage: None,
+                repository_subpath: None,
+            };
+        }
 
-    fn get_crate_desc(crate_name: &str, version: Option<&Version>) -> String {
-        if let Some(version) = version {
-            format!("{crate_name}@{version}")
-        } else {
-            crate_name.into()
+        let persistent_key = self.persistent_key(crate_name, version, registry);
+        let ttl = version.is_none().then_some(LATEST_TTL);
+        if let Some(info) =
+            self.persistent_cache
+                .get::<CrateInfo>(CacheKind::CrateInfo, &persistent_key, ttl)
+        {
+            self.cache_crate_info(cache_key, info.clone());
+            return info;
         }
-    }
 
-    fn extract_crate_info(
-        crate_name: &str,
-        crate_desc: String,
-        version: Option<&Version>,
-    ) -> CrateInfo {
-        let cargo_runner = CargoRunner::new(None);
-        let output = match cargo_runner.run("info", ["--color", "never", &crate_desc]) {
-            Ok(output) => output,
+        let info = match self.crate_info_from_backend(crate_name, version, registry) {
+            Ok(info) => {
+                // repository may be missing only because the crate sources are not available yet
+                if info.repository.is_some() {
+                    self.persistent_cache
+                        .put(CacheKind::CrateInfo, &persistent_key, &info);
+                }
+                info
+            }
             Err(err) => {
-                eprintln!("[ERROR] 'cargo info {crate_desc}' command failed. Error: {err}");
-                return CrateInfo {
+                eprintln!("[WARN] Cannot get '{crate_desc}' crate info. Error: {err:#}");
+                let metadata = version
+                    .map(|version| self.crate_metadata(crate_name, version, registry, None))
+                    .unwrap_or_default();
+                CrateInfo {
                     version: version.cloned(),
-                    repository: None,
-                };
+                    repository: metadata.repository_url(),
+                    repository_subpath: metadata.repository_subpath(),
+                    homepage: metadata.homepage,
+                }
             }
         };
+        self.cache_crate_info(cache_key, info.clone());
+        info
+    }
 
-        let version = Self::extract_version(crate_name, &output, version.is_none());
-        let repository = Self::repository_from_output(&output);
-        if repository.is_none() {
-            // TODO: Get repository in other way
-            eprintln!("[ERROR] Cannot get repository of the '{crate_name}' crate:\n{output}");
+    /// Key of the crate info in the persistent cache. Specific version info never changes
+    fn persistent_key(
+        &self,
+        crate_name: &str,
+        version: Option<&Version>,
+        registry: &RegistrySource,
+    ) -> String {
+        let crate_desc = Self::get_crate_desc(crate_name, version);
+        let mut persistent_key = format!("{registry}|{crate_desc}");
+        if version.is_none() {
+            // the latest version depends on the registry replacement and the pre-releases
+            if let Some(replacement) = self.replacement(registry) {
+                persistent_key.push_str(&format!("|{replacement}"));
+            }
+            if self.pre {
+                persistent_key.push_str("|pre");
+            }
         }
+        persistent_key
+    }
 
-        CrateInfo {
-            version,
-            repository,
+    /// Latest crate info from the in-memory or the persistent cache
+    fn cached_latest_info(&self, crate_name: &str, registry: &RegistrySource) -> Option<CrateInfo> {
+        self.cached_crate_info(&(registry.clone(), crate_name.to_string()))
+            .or_else(|| {
+                self.persistent_cache.get::<CrateInfo>(
+                    CacheKind::CrateInfo,
+                    &self.persistent_key(crate_name, None, registry),
+                    Some(LATEST_TTL),
+                )
+            })
+    }
+
+    fn cached_crate_info(&self, cache_key: &(RegistrySource, String)) -> Option<CrateInfo> {
+        let cache = self
+            .crate_info_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        cache.get(cache_key).cloned()
+    }
+
+    fn cache_crate_info(&self, cache_key: (RegistrySource, String), info: CrateInfo) {
+        let mut cache = self
+            .crate_info_cache
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        cache.insert(cache_key, info);
+    }
+
+    fn crate_info_from_backend(
+        &self,
+        crate_name: &str,
+        version: Option<&Version>,
+        registry: &RegistrySource,
+    ) -> Result<CrateInfo> {
+        let backend = self
+            .backend(registry)
+            .ok_or_else(|| anyhow!("Unknown registry '{registry}'"))?;
+
+        if version.is_none() {
+            // latest version may be missing in the local cache
+            self.refresh_crate(crate_name, registry, backend.as_ref());
+        }
+        let mut versions = backend.list_versions(crate_name)?;
+        if let Some(version) = version
+            && !versions.iter().any(|v| v.version == *version)
+        {
+            // version may be published after the local cache was updated
+            backend.refresh(crate_name);
+            versions = backend.list_versions(crate_name)?;
         }
+
+        let version = match version {
+            Some(version) => versions
+                .iter()
+                .find(|v| v.version == *version)
+                .map(|v| v.version.clone())
+                .ok_or_else(|| anyhow!("Version {version} is not published"))?,
+            None => self.pick_latest(&versions)?,
+        };
+        let metadata = self.crate_metadata(crate_name, &version, registry, Some(backend.as_ref()));
+
+        Ok(CrateInfo {
+            version: Some(version),
+            repository: metadata.repository_url(),
+            repository_subpath: metadata.repository_subpath(),
+            homepage: metadata.homepage,
+        })
     }
 
-    fn extract_version(crate_name: &str, output: &str, latest: bool) -> Option<Version> {
-        output.lines().find_map(|l| {
-            l.trim().strip_prefix("version: ").and_then(|version_desc| {
-                let version_str = if let Some((cur_version, latest_version)) =
-                    version_desc.trim().split_once(" (latest ")
-                {
-                    if latest {
-                        &latest_version[..latest_version.len() - 1]
-                    } else {
-                        cur_version
-                    }
-                } else if let Some((cur_version, _)) = version_desc.split_once(" ") {
-                    cur_version
-                } else {
-                    version_desc
-                };
+    /// The latest published version. Pre-releases are picked only if they are allowed
+    fn pick_latest(&self, versions: &[VersionInfo]) -> Result<Version> {
+        latest_version(versions, self.pre).ok_or_else(|| {
+            if self.pre {
+                anyhow!("There are no published versions")
+            } else {
+                anyhow!("There are no published stable versions. Use `--pre` to allow pre-releases")
+            }
+        })
+    }
 
-                // load crate version into the local registry if it's not yet there
-                let cargo_runner = CargoRunner::new(None);
-                if let Err(err) = cargo_runner.run("info", [format!("{crate_name}@{version_str}")])
-                {
-                    eprintln!(
-                        "[ERROR] 'cargo info {crate_name}@{version_str}' command failed. Error: {err}"
-                    );
-                }
+    /// Repository and homepage from the manifest of the vendored or locally available crate.
+    /// Falls back to the registry backend
+    fn crate_metadata(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        registry: &RegistrySource,
+        backend: Option<&dyn RegistryBackend>,
+    ) -> CrateMetadata {
+        if let Some(crate_path) = self.get_crate_path(crate_name, version, registry)
+            && let Ok(metadata) = CrateMetadata::read(&crate_path)
+        {
+            return metadata;
+        }
 
-                match Version::parse(version_str) {
-                    Ok(version) => Some(version),
-                    Err(err) => {
-                        eprintln!(
-                            "[WARN] Cannot parse '{crate_name}' version '{version_str}'. Error: {err}"
-                        );
-                        None
-                    }
-                }
-            })
+        let result = match backend {
+            Some(backend) => backend.crate_metadata(crate_name, version),
+            None => Err(anyhow!("Crate sources are not available")),
+        };
+        result.unwrap_or_else(|err| {
+            eprintln!(
+                "[WARN] Repository of the '{crate_name}@{version}' crate is not available. Error: {err:#}"
+            );
+            CrateMetadata::default()
         })
     }
 
-    fn repository_from_output(output: &str) -> Option<String> {
-        output
-            .lines()
-            .find_map(|l| l.strip_prefix("repository: "))
-            .map(|r| r.trim_end_matches("/").into())
-            .or_else(|| {
-                if let Some(homepage) = output.lines().find_map(|l| l.strip_prefix("homepage: "))
-                    && homepage.starts_with("https://github.com/")
-                {
-                    let parts: Vec<_> = homepage.trim_end_matches("/").split('/').collect();
-                    if parts.len() >= 5 {
-                        return Some(parts[..5].join("/"));
-                    }
-                }
-                // TODO: extract repository from the other sources
-                None
-            })
+    fn get_crate_desc(crate_name: &str, version: Option<&Version>) -> String {
+        if let Some(version) = version {
+            format!("{crate_name}@{version}")
+        } else {
+            crate_name.into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry_manager::CrateSource;
+
+    #[test]
+    fn test_crate_source_cache_key() {
+        assert_eq!(
+            Some("registry|crates-io|foo@1.0.0".to_string()),
+            CrateSource::Registry.cache_key("crates-io|foo@1.0.0")
+        );
+        assert_eq!(
+            Some("vendored|crates-io|foo@1.0.0".to_string()),
+            CrateSource::Vendored.cache_key("crates-io|foo@1.0.0")
+        );
+        assert_eq!(None, CrateSource::Verified.cache_key("crates-io|foo@1.0.0"));
     }
 }
@@ -0,0 +1,133 @@
+use crate::git_source::GitSource;
+use crate::package_id_info::PackageIdInfo;
+use crate::package_source::PackageSource;
+
+/// Index URL of the crates.io registry as it's shown in the package id
+pub const CRATES_IO_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+
+/// Sparse index URL of the crates.io registry
+pub const CRATES_IO_SPARSE_INDEX: &str = "sparse+https://index.crates.io/";
+
+/// Registry the crate is published to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RegistrySource {
+    /// Default crates.io registry
+    #[default]
+    CratesIo,
+    /// Registry by name from the cargo configuration
+    Named(String),
+    /// Registry by index URL. Sparse registry URL starts with `sparse+`
+    Index(String),
+    /// Git repository of the git dependency. Crate versions are identified by the commit
+    Git(GitSource),
+}
+
+impl std::fmt::Display for RegistrySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CratesIo => write!(f, "crates.io"),
+            Self::Named(name) => write!(f, "{name}"),
+            Self::Index(index) => write!(f, "{index}"),
+            Self::Git(source) => write!(f, "{source}"),
+        }
+    }
+}
+
+impl RegistrySource {
+    /// Registry of the package from the cargo metadata
+    pub fn from_package(pkg_info: &PackageIdInfo) -> Self {
+        match pkg_info.source {
+            PackageSource::Registry if pkg_info.path == CRATES_IO_INDEX => Self::CratesIo,
+            PackageSource::Registry => Self::Index(pkg_info.path.clone()),
+            PackageSource::Sparse
+                if format!("sparse+{}", pkg_info.path) == CRATES_IO_SPARSE_INDEX =>
+            {
+                Self::CratesIo
+            }
+            PackageSource::Sparse => Self::Index(format!("sparse+{}", pkg_info.path)),
+            PackageSource::Git => Self::Git(GitSource::parse(&pkg_info.path)),
+            _ => Self::CratesIo,
+        }
+    }
+
+    /// Registry from the command line
+    pub fn from_name(name: Option<&str>) -> Self {
+        match name {
+            None | Some("crates-io") => Self::CratesIo,
+            Some(name) => Self::Named(name.into()),
+        }
+    }
+
+    /// Arguments of the cargo commands (`cargo info`) to select the registry
+    pub fn cargo_args(&self) -> Vec<String> {
+        match self {
+            Self::CratesIo | Self::Git(_) => vec![],
+            Self::Named(name) => vec!["--registry".into(), name.clone()],
+            Self::Index(index) => vec!["--index".into(), index.clone()],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::package_id_info::PackageIdInfo;
+    use crate::package_source::PackageSource;
+    use crate::registry_source::RegistrySource;
+
+    fn package(source: PackageSource, path: &str) -> PackageIdInfo {
+        PackageIdInfo {
+            name: "foo".into(),
+            version: semver::Version::new(1, 0, 0),
+            source,
+            path: path.into(),
+            commit: None,
+        }
+    }
+
+    #[test]
+    fn test_from_package() {
+        assert_eq!(
+            RegistrySource::CratesIo,
+            RegistrySource::from_package(&package(
+                PackageSource::Registry,
+                "https://github.com/rust-lang/crates.io-index"
+            ))
+        );
+        assert_eq!(
+            RegistrySource::CratesIo,
+            RegistrySource::from_package(&package(
+                PackageSource::Sparse,
+                "https://index.crates.io/"
+            ))
+        );
+        assert_eq!(
+            RegistrySource::Index("sparse+https://my-registry.com/index/".into()),
+            RegistrySource::from_package(&package(
+                PackageSource::Sparse,
+                "https://my-registry.com/index/"
+            ))
+        );
+        assert!(matches!(
+            RegistrySource::from_package(&package(
+                PackageSource::Git,
+                "https://github.com/foo/foo?branch=main"
+            )),
+            RegistrySource::Git(_)
+        ));
+    }
+
+    #[test]
+    fn test_cargo_args() {
+        assert!(
+            RegistrySource::from_name(Some("crates-io"))
+                .cargo_args()
+                .is_empty()
+        );
+        assert_eq!(
+            ["--registry", "my-registry"],
+            RegistrySource::from_name(Some("my-registry"))
+                .cargo_args()
+                .as_slice()
+        );
+    }
+}
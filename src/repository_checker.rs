@@ -0,0 +1,177 @@
+use semver::Version;
+
+use crate::crate_diff_info::CrateDiffInfo;
+use crate::diff_report::DiffReport;
+
+/// Repository change between 2 versions of the crate.
+/// Moving the crate to another owner or dropping the repository link is a common sign of the crate takeover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryChange {
+    pub name: String,
+    pub from_version: Version,
+    pub to_version: Version,
+    /// Missing if the repository is added in the target version
+    pub from_repository: Option<String>,
+    /// Missing if the repository is removed in the target version
+    pub to_repository: Option<String>,
+    pub from_homepage: Option<String>,
+    pub to_homepage: Option<String>,
+    /// Host or owner of the repository is changed, not only its name. Never set for added or removed repositories
+    pub owner_changed: bool,
+}
+
+impl RepositoryChange {
+    /// Repository link is added or removed, or it cannot be resolved for one of the versions
+    pub fn added_or_removed(&self) -> bool {
+        self.from_repository.is_none() || self.to_repository.is_none()
+    }
+
+    pub fn severity(&self) -> &'static str {
+        if self.added_or_removed() {
+            "medium"
+        } else {
+            "high"
+        }
+    }
+}
+
+/// Returns repository changes of all updated crates in the report
+pub fn check_repositories(report: &DiffReport) -> Vec<RepositoryChange> {
+    report
+        .crate_diffs()
+        .into_iter()
+        .filter_map(repository_change)
+        .collect()
+}
+
+fn repository_change(diff: &CrateDiffInfo) -> Option<RepositoryChange> {
+    let from_version = diff.from_version.as_ref()?;
+    let to_version = diff.to_version.as_ref()?;
+    // repository URLs are normalized. Hosts and owners are case insensitive
+    let from_repository_lower = diff.from_repository.as_deref().map(str::to_lowercase);
+    let to_repository_lower = diff.to_repository.as_deref().map(str::to_lowercase);
+    if from_repository_lower == to_repository_lower {
+        return None;
+    }
+
+    Some(RepositoryChange {
+        name: diff.name.clone(),
+        from_version: from_version.clone(),
+        to_version: to_version.clone(),
+        from_repository: diff.from_repository.clone(),
+        to_repository: diff.to_repository.clone(),
+        from_homepage: diff.from_homepage.clone(),
+        to_homepage: diff.to_homepage.clone(),
+        owner_changed: from_repository_lower
+            .as_deref()
+            .zip(to_repository_lower.as_deref())
+            .is_some_and(|(from, to)| owner(from) != owner(to)),
+    })
+}
+
+/// Host and owner (with the nested groups) of the repository: `https://github.com/owner/repo` -> `github.com/owner`
+fn owner(repository: &str) -> &str {
+    let path = repository
+        .split_once("://")
+        .map(|(_, path)| path)
+        .unwrap_or(repository);
+    path.rsplit_once('/')
+        .map(|(owner, _)| owner)
+        .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::crate_diff_info::CrateDiffInfo;
+    use crate::registry_source::RegistrySource;
+    use crate::repository_checker::repository_change;
+
+    fn crate_diff(from_repository: &str, to_repository: &str) -> CrateDiffInfo {
+        CrateDiffInfo {
+            name: "foo".into(),
+            from_version: Some(Version::new(1, 0, 0)),
+            from_hash: None,
+            to_version: Some(Version::new(1, 1, 0)),
+            to_hash: None,
+            from_yanked: false,
+            to_yanked: false,
+            repository: Some(to_repository.into()),
+            from_repository: Some(from_repository.into()),
+            to_repository: Some(to_repository.into()),
+            from_homepage: None,
+            to_homepage: None,
+            path_in_vcs: None,
+            registry: RegistrySource::CratesIo,
+        }
+    }
+
+    #[test]
+    fn test_repository_change() {
+        assert_eq!(
+            None,
+            repository_change(&crate_diff(
+                "https://github.com/org/foo",
+                "https://github.com/Org/foo"
+            ))
+        );
+
+        let change = repository_change(&crate_diff(
+            "https://github.com/org/foo",
+            "https://github.com/org/foo-rs",
+        ))
+        .unwrap();
+        assert!(!change.owner_changed);
+
+        let change = repository_change(&crate_diff(
+            "https://github.com/org/foo",
+            "https://github.com/attacker/foo",
+        ))
+        .unwrap();
+        assert!(change.owner_changed);
+        assert!(!change.added_or_removed());
+        assert_eq!("high", change.severity());
+        assert_eq!(
+            Some("https://github.com/attacker/foo"),
+            change.to_repository.as_deref()
+        );
+
+        let change = repository_change(&crate_diff(
+            "https://github.com/org/foo",
+            "https://gitlab.com/org/foo",
+        ))
+        .unwrap();
+        assert!(change.owner_changed);
+
+        let mut added = crate_diff("https://github.com/org/foo", "https://github.com/org/bar");
+        added.from_version = None;
+        assert_eq!(None, repository_change(&added));
+    }
+
+    #[test]
+    fn test_repository_added_or_removed() {
+        let mut removed = crate_diff("https://github.com/org/foo", "https://github.com/org/foo");
+        removed.to_repository = None;
+        let change = repository_change(&removed).unwrap();
+        assert!(!change.owner_changed);
+        assert!(change.added_or_removed());
+        assert_eq!("medium", change.severity());
+        assert_eq!(None, change.to_repository);
+
+        let mut added = crate_diff("https://github.com/org/foo", "https://github.com/org/foo");
+        added.from_repository = None;
+        let change = repository_change(&added).unwrap();
+        assert!(!change.owner_changed);
+        assert!(change.added_or_removed());
+        assert_eq!(
+            Some("https://github.com/org/foo"),
+            change.to_repository.as_deref()
+        );
+
+        let mut missing = crate_diff("https://github.com/org/foo", "https://github.com/org/foo");
+        missing.from_repository = None;
+        missing.to_repository = None;
+        assert_eq!(None, repository_change(&missing));
+    }
+}
@@ -0,0 +1,221 @@
+/// Hosts that keep repositories only on the `<owner>/<repo>` level. Other hosts (e.g. GitLab) may have nested groups
+const TWO_LEVEL_HOSTS: [&str; 4] = ["github.com", "codeberg.org", "bitbucket.org", "git.sr.ht"];
+
+/// Path segments of the web UI that separate the repository path from the path inside the repository:
+/// `tree`/`blob` (GitHub, sourcehut), `-` (GitLab), `src` (Gitea, Bitbucket)
+const SUBPATH_MARKERS: [&str; 4] = ["tree", "blob", "-", "src"];
+
+/// Repository URL split into the canonical repository URL and the path inside the repository
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryUrl {
+    /// Canonical repository URL: `https://<host>/<owner>/<repo>`
+    pub url: String,
+    /// Path inside the repository taken from the `tree`/`blob` link: `crates/foo`
+    pub subpath: Option<String>,
+}
+
+impl RepositoryUrl {
+    /// Canonicalizes scheme, host and repository path of the URL and strips the `.git` and `tree`/`blob` suffixes.
+    /// URLs that cannot be parsed are returned without trailing slashes
+    pub fn parse(url: &str) -> Self {
+        let url = url.trim().trim_end_matches('/');
+        let Some((host, path)) = split_host(url) else {
+            return Self {
+                url: url.into(),
+                subpath: None,
+            };
+        };
+        let host = host.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        let repo_len = if TWO_LEVEL_HOSTS.contains(&host) {
+            segments.len().min(2)
+        } else {
+            segments
+                .iter()
+                .skip(2)
+                .position(|segment| SUBPATH_MARKERS.contains(segment))
+                .map(|pos| pos + 2)
+                .unwrap_or(segments.len())
+        };
+        let mut repo_path = segments[..repo_len].join("/");
+        if let Some(stripped) = repo_path.strip_suffix(".git") {
+            repo_path = stripped.into();
+        }
+
+        Self {
+            url: format!("https://{host}/{repo_path}")
+                .trim_end_matches('/')
+                .into(),
+            subpath: subpath(&segments[repo_len..]),
+        }
+    }
+}
+
+/// Canonical repository URL without the path inside the repository
+pub fn normalize_repository(url: &str) -> String {
+    RepositoryUrl::parse(url).url
+}
+
+/// Splits URL into the host and the path. Supports `https`, `http`, `git`, `ssh` and scp-like `git@host:path` URLs.
+/// Web UI port is kept for `http(s)` URLs only
+fn split_host(url: &str) -> Option<(&str, &str)> {
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    let (authority, path, keep_port) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let scheme = scheme.to_lowercase();
+            if !["https", "http", "git", "ssh"].contains(&scheme.as_str()) {
+                return None;
+            }
+            let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+            (authority, path, scheme.starts_with("http"))
+        }
+        None => {
+            // git@github.com:org/repo
+            let (authority, path) = url.split_once(':')?;
+            if !authority.contains('@') {
+                return None;
+            }
+            (authority, path, false)
+        }
+    };
+
+    let host = authority.rsplit('@').next()?;
+    let host = if keep_port {
+        host
+    } else {
+        host.split(':').next()?
+    };
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((host, path))
+}
+
+/// Path inside the repository from the web UI link segments after the repository path:
+/// `tree/<ref>/<path>`, `-/tree/<ref>/<path>`, `src/branch/<ref>/<path>`, `tree/<ref>/item/<path>`
+fn subpath(segments: &[&str]) -> Option<String> {
+    let mut segments = segments;
+    if let [first, rest @ ..] = segments
+        && *first == "-"
+    {
+        segments = rest;
+    }
+    let [kind, rest @ ..] = segments else {
+        return None;
+    };
+    if !SUBPATH_MARKERS.contains(kind) {
+        return None;
+    }
+    segments = rest;
+    // Gitea: src/branch/<ref>, src/tag/<ref>, src/commit/<ref>
+    if *kind == "src"
+        && let [ref_kind, rest @ ..] = segments
+        && ["branch", "tag", "commit"].contains(ref_kind)
+    {
+        segments = rest;
+    }
+    // skip the ref
+    let [_, rest @ ..] = segments else {
+        return None;
+    };
+    segments = rest;
+    // sourcehut: tree/<ref>/item/<path>
+    if let ["item", rest @ ..] = segments {
+        segments = rest;
+    }
+
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::repository_url::{RepositoryUrl, normalize_repository};
+
+    #[test]
+    fn test_normalize_repository() {
+        for url in [
+            "https://github.com/org/repo",
+            "https://github.com/org/repo/",
+            "https://github.com/org/repo.git",
+            "http://github.com/org/repo",
+            "https://www.github.com/org/repo",
+            "https://GitHub.com/org/repo",
+            "git@github.com:org/repo",
+            "git@github.com:org/repo.git",
+            "ssh://git@github.com/org/repo.git",
+            "git+https://github.com/org/repo.git",
+            "git://github.com/org/repo",
+            "https://github.com/org/repo#readme",
+            "https://github.com/org/repo/issues",
+        ] {
+            assert_eq!(
+                "https://github.com/org/repo",
+                normalize_repository(url),
+                "{url}"
+            );
+        }
+
+        assert_eq!(
+            "https://gitlab.com/group/subgroup/repo",
+            normalize_repository("git@gitlab.com:group/subgroup/repo.git")
+        );
+        assert_eq!(
+            "https://git.sr.ht/~user/repo",
+            normalize_repository("https://git.sr.ht/~user/repo/tree/main/item/crates/foo")
+        );
+        assert_eq!(
+            "https://git.example.com:8443/org/repo",
+            normalize_repository("https://git.example.com:8443/org/repo/")
+        );
+        assert_eq!("not a url", normalize_repository("not a url"));
+    }
+
+    #[test]
+    fn test_repository_subpath() {
+        for (url, expected_url, expected_subpath) in [
+            (
+                "https://github.com/org/repo/tree/master/crates/foo",
+                "https://github.com/org/repo",
+                Some("crates/foo"),
+            ),
+            (
+                "https://github.com/org/repo/blob/main/crates/foo/",
+                "https://github.com/org/repo",
+                Some("crates/foo"),
+            ),
+            (
+                "https://gitlab.com/group/subgroup/repo/-/tree/main/crates/foo",
+                "https://gitlab.com/group/subgroup/repo",
+                Some("crates/foo"),
+            ),
+            (
+                "https://codeberg.org/org/repo/src/branch/main/crates/foo",
+                "https://codeberg.org/org/repo",
+                Some("crates/foo"),
+            ),
+            (
+                "https://bitbucket.org/org/repo/src/master/crates/foo",
+                "https://bitbucket.org/org/repo",
+                Some("crates/foo"),
+            ),
+            (
+                "https://github.com/org/repo/tree/master",
+                "https://github.com/org/repo",
+                None,
+            ),
+        ] {
+            assert_eq!(
+                RepositoryUrl {
+                    url: expected_url.into(),
+                    subpath: expected_subpath.map(Into::into),
+                },
+                RepositoryUrl::parse(url),
+                "{url}"
+            );
+        }
+    }
+}
@@ -1,23 +1,34 @@
 use std::collections::BTreeSet;
 
+use crate::api_comparator::{ApiDiff, ApiItem};
+use crate::crate_archive::{ChecksumMismatch, ChecksumSource};
 use crate::crate_diff_info::CrateDiffInfo;
 use crate::dependency_diff::DependencyDiff;
 use crate::diff_report::DiffReport;
 use crate::field_size::get_dep_max_len;
+use crate::forge::ForgeLinks;
+use crate::license_checker::LicenseDiff;
+use crate::package_inspector::PackageAnomalies;
+use crate::registry_source::RegistrySource;
+use crate::repository_checker::RepositoryChange;
+use crate::size_comparator::{PackageSize, SizeDiff, SizeReport, format_size};
+use crate::source_comparator::SourceDiff;
 
 pub struct SimpleReportPrinter {
     group: bool,
     diff_rs: bool,
+    forge_links: ForgeLinks,
     max_name_len: usize,
     max_from_ver_len: usize,
     max_to_ver_len: usize,
 }
 
 impl SimpleReportPrinter {
-    pub fn new(group: bool, diff_rs: bool) -> Self {
+    pub fn new(group: bool, diff_rs: bool, forge_links: ForgeLinks) -> Self {
         Self {
             group,
             diff_rs,
+            forge_links,
             max_name_len: 0,
             max_from_ver_len: 0,
             max_to_ver_len: 0,
@@ -54,6 +65,65 @@ impl SimpleReportPrinter {
                 self.print_diffs(diffs);
             }
         }
+
+        let yanked_diffs: Vec<_> = report
+            .crate_diffs()
+            .into_iter()
+            .filter(|diff| diff.from_yanked || diff.to_yanked)
+            .collect();
+        if !yanked_diffs.is_empty() {
+            println!();
+            for diff in yanked_diffs {
+                self.print_yanked(diff);
+            }
+        }
+
+        if !report.checksum_mismatches.is_empty() {
+            println!();
+            for mismatch in &report.checksum_mismatches {
+                self.print_checksum_mismatch(mismatch);
+            }
+        }
+
+        if !report.repository_changes.is_empty() {
+            println!();
+            for change in &report.repository_changes {
+                self.print_repository_change(change);
+            }
+        }
+
+        if !report.license_diffs.is_empty() {
+            println!();
+            for diff in &report.license_diffs {
+                self.print_license_diff(diff);
+            }
+        }
+
+        if !report.source_diffs.is_empty() {
+            println!();
+            for diff in &report.source_diffs {
+                self.print_source_diff(diff);
+            }
+        }
+
+        if !report.api_diffs.is_empty() {
+            println!();
+            for diff in &report.api_diffs {
+                self.print_api_diff(diff);
+            }
+        }
+
+        if !report.package_anomalies.is_empty() {
+            println!();
+            for anomalies in &report.package_anomalies {
+                self.print_package_anomalies(anomalies);
+            }
+        }
+
+        if let Some(size_report) = &report.size_report {
+            println!();
+            self.print_size_report(size_report);
+        }
     }
 
     fn print_grouped_diff(&self, diff: &DependencyDiff) {
@@ -123,23 +193,18 @@ impl SimpleReportPrinter {
             "{prefix} {:1$} {from_version_str:2$} {to_version_str:3$} ",
             diff.name, self.max_name_len, self.max_from_ver_len, self.max_to_ver_len
         );
+        // diff.rs knows only the published crates
+        let diff_rs = self.diff_rs && !matches!(diff.registry, RegistrySource::Git(_));
 
         if diff.from_version.is_some() {
             if diff.to_version.is_some() {
-                if self.diff_rs {
+                if diff_rs {
                     println!(
                         "https://diff.rs/{}/{from_version_str}/{to_version_str}",
                         diff.name
                     );
-                } else if let Some(repository) = &diff.repository
-                    && repository.starts_with("https://github.com/")
-                    && let (Some(from_hash), Some(to_hash)) = (&diff.from_hash, &diff.to_hash)
-                {
-                    println!(
-                        "{repository}/compare/{}...{}",
-                        &from_hash[..7],
-                        &to_hash[..7]
-                    );
+                } else if let Some(link) = self.forge_links.compare_link(diff) {
+                    println!("{link}");
                 } else {
                     let repository = if let Some(repository) = &diff.repository {
                         repository
@@ -150,12 +215,14 @@ impl SimpleReportPrinter {
                 }
             } else {
                 // removed dependency
-                if !self.diff_rs
-                    && let Some(repository) = &diff.repository
-                    && repository.starts_with("https://github.com/")
-                    && let Some(from_hash) = &diff.from_hash
+                if !diff_rs
+                    && let Some(link) = self.forge_links.commit_link(
+                        diff,
+                        diff.from_hash.as_deref(),
+                        diff.from_version.as_ref(),
+                    )
                 {
-                    println!("{repository}/commit/{from_hash}");
+                    println!("{link}");
                 } else {
                     println!(
                         "https://diff.rs/{}/{from_version_str}/{from_version_str}",
@@ -165,12 +232,14 @@ impl SimpleReportPrinter {
             }
         } else if diff.to_version.is_some() {
             // added dependency
-            if !self.diff_rs
-                && let Some(repository) = &diff.repository
-                && repository.starts_with("https://github.com/")
-                && let Some(to_hash) = &diff.to_hash
+            if !diff_rs
+                && let Some(link) = self.forge_links.commit_link(
+                    diff,
+                    diff.to_hash.as_deref(),
+                    diff.to_version.as_ref(),
+                )
             {
-                println!("{repository}/commit/{to_hash}");
+                println!("{link}");
             } else {
                 println!(
                     "https://diff.rs/{}/{to_version_str}/{to_version_str}",
@@ -182,4 +251,249 @@ impl SimpleReportPrinter {
             println!("{}", diff.repository.as_deref().unwrap_or("<unknown>"));
         }
     }
+
+    fn print_yanked(&self, diff: &CrateDiffInfo) {
+        let message = match (diff.from_yanked, diff.to_yanked) {
+            (true, true) => "current and target versions are yanked",
+            (true, false) => "current version is yanked",
+            _ => "target version is yanked",
+        };
+        let from_version_str = diff
+            .from_version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let to_version_str = diff
+            .to_version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+
+        println!(
+            "Y {:1$} {from_version_str:2$} {to_version_str:3$} {message}",
+            diff.name, self.max_name_len, self.max_from_ver_len, self.max_to_ver_len
+        );
+    }
+
+    fn print_checksum_mismatch(&self, mismatch: &ChecksumMismatch) {
+        let version_str = mismatch.version.to_string();
+
+        if mismatch.source == ChecksumSource::Missing {
+            println!(
+                "C {:1$} {version_str:2$} no checksum to verify against, actual {3} [critical]",
+                mismatch.name, self.max_name_len, self.max_to_ver_len, mismatch.actual,
+            );
+            return;
+        }
+        println!(
+            "C {:1$} {version_str:2$} {3}: expected {4}, actual {5} [critical]",
+            mismatch.name,
+            self.max_name_len,
+            self.max_to_ver_len,
+            mismatch.source.as_str(),
+            mismatch.expected,
+            mismatch.actual,
+        );
+    }
+
+    fn print_repository_change(&self, change: &RepositoryChange) {
+        let from_version_str = change.from_version.to_string();
+        let to_version_str = change.to_version.to_string();
+        let message = match (&change.from_repository, &change.to_repository) {
+            (None, Some(to_repository)) => format!("repository added: {to_repository}"),
+            (Some(from_repository), None) => format!("repository removed: {from_repository}"),
+            (from_repository, to_repository) => {
+                let owner_changed = if change.owner_changed {
+                    " (owner changed)"
+                } else {
+                    ""
+                };
+                format!(
+                    "{} -> {}{owner_changed}",
+                    from_repository.as_deref().unwrap_or("<no-repository>"),
+                    to_repository.as_deref().unwrap_or("<no-repository>"),
+                )
+            }
+        };
+
+        println!(
+            "R {:1$} {from_version_str:2$} {to_version_str:3$} {message} [{4}]",
+            change.name,
+            self.max_name_len,
+            self.max_from_ver_len,
+            self.max_to_ver_len,
+            change.severity(),
+        );
+    }
+
+    fn print_license_diff(&self, diff: &LicenseDiff) {
+        let from_version_str = diff
+            .from_version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let to_version_str = diff.to_version.to_string();
+        let to_license = diff.to_license.as_deref().unwrap_or("<unknown-license>");
+
+        print!(
+            "L {:1$} {from_version_str:2$} {to_version_str:3$} ",
+            diff.name, self.max_name_len, self.max_from_ver_len, self.max_to_ver_len
+        );
+
+        if diff.from_version.is_some() {
+            let from_license = diff.from_license.as_deref().unwrap_or("<unknown-license>");
+            print!("{from_license} -> {to_license}");
+            if !diff.changed_files.is_empty() {
+                print!(" (changed files: {})", diff.changed_files.join(", "));
+            }
+        } else {
+            // added dependency
+            print!("{to_license}");
+        }
+
+        if diff.allowed {
+            println!();
+        } else {
+            println!(" [not allowed]");
+        }
+    }
+
+    fn print_source_diff(&self, diff: &SourceDiff) {
+        let from_version_str = diff.from_version.to_string();
+        let to_version_str = diff.to_version.to_string();
+
+        println!(
+            "S {:1$} {from_version_str:2$} {to_version_str:3$} {4} files changed, +{5} -{6}",
+            diff.name,
+            self.max_name_len,
+            self.max_from_ver_len,
+            self.max_to_ver_len,
+            diff.files.len(),
+            diff.added_lines(),
+            diff.removed_lines(),
+        );
+
+        for file in &diff.files {
+            if let Some(unified_diff) = &file.unified_diff {
+                print!("{unified_diff}");
+            }
+        }
+    }
+
+    fn print_api_diff(&self, diff: &ApiDiff) {
+        for item in &diff.added {
+            self.print_api_item(diff, item, "+", "");
+        }
+
+        let marker = if diff.semver_violation {
+            " [semver violation]"
+        } else {
+            ""
+        };
+        for item in &diff.removed {
+            self.print_api_item(diff, item, "-", marker);
+        }
+
+        for (_, item) in &diff.changed {
+            self.print_api_item(diff, item, "~", "");
+        }
+    }
+
+    fn print_api_item(&self, diff: &ApiDiff, item: &ApiItem, change: &str, marker: &str) {
+        let from_version_str = diff.from_version.to_string();
+        let to_version_str = diff.to_version.to_string();
+
+        println!(
+            "A {:1$} {from_version_str:2$} {to_version_str:3$} {change} {4} {5}{marker}",
+            diff.name,
+            self.max_name_len,
+            self.max_from_ver_len,
+            self.max_to_ver_len,
+            item.kind,
+            item.path,
+        );
+    }
+
+    fn print_package_anomalies(&self, anomalies: &PackageAnomalies) {
+        let version_str = anomalies.version.to_string();
+
+        for anomaly in &anomalies.anomalies {
+            println!(
+                "! {:1$} {version_str:2$} {3}: {4}",
+                anomalies.name,
+                self.max_name_len,
+                self.max_to_ver_len,
+                anomaly.kind(),
+                anomaly.path(),
+            );
+        }
+    }
+
+    fn print_size_report(&self, size_report: &SizeReport) {
+        for diff in &size_report.diffs {
+            self.print_size_diff(diff);
+        }
+
+        println!(
+            "Z total: {}",
+            format_size_change(
+                Some(&size_report.from_total),
+                Some(&size_report.to_total),
+                size_report.growth_percent()
+            )
+        );
+    }
+
+    fn print_size_diff(&self, diff: &SizeDiff) {
+        let from_version_str = diff
+            .from_version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let to_version_str = diff
+            .to_version
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let marker = if diff.over_threshold {
+            " [over threshold]"
+        } else {
+            ""
+        };
+
+        println!(
+            "Z {:1$} {from_version_str:2$} {to_version_str:3$} {4}{marker}",
+            diff.name,
+            self.max_name_len,
+            self.max_from_ver_len,
+            self.max_to_ver_len,
+            format_size_change(
+                diff.from_size.as_ref(),
+                diff.to_size.as_ref(),
+                diff.growth_percent()
+            ),
+        );
+    }
+}
+
+fn format_size_change(
+    from_size: Option<&PackageSize>,
+    to_size: Option<&PackageSize>,
+    growth_percent: Option<f64>,
+) -> String {
+    let bytes =
+        |size: Option<&PackageSize>| size.map(|s| format_size(s.bytes)).unwrap_or("-".into());
+    let files =
+        |size: Option<&PackageSize>| size.map(|s| s.files.to_string()).unwrap_or("-".into());
+    let growth = growth_percent
+        .map(|g| format!(" ({g:+.1}%)"))
+        .unwrap_or_default();
+
+    format!(
+        "{} -> {}{growth}, {} -> {} files",
+        bytes(from_size),
+        bytes(to_size),
+        files(from_size),
+        files(to_size)
+    )
 }
@@ -0,0 +1,300 @@
+use std::path::Path;
+
+use semver::Version;
+
+use crate::config::Config;
+use crate::crate_files::list_crate_files;
+use crate::diff_report::DiffReport;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// Unpacked package size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PackageSize {
+    /// Number of files
+    pub files: usize,
+    /// Total size of all files in bytes
+    pub bytes: u64,
+}
+
+impl PackageSize {
+    fn add(&mut self, other: &PackageSize) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Unpacked size change between 2 versions of the crate
+#[derive(Debug, Clone)]
+pub struct SizeDiff {
+    /// Crate name
+    pub name: String,
+    /// Initial crate version. `None` for the added crate
+    pub from_version: Option<Version>,
+    /// Target crate version. `None` for the removed crate
+    pub to_version: Option<Version>,
+    pub from_size: Option<PackageSize>,
+    pub to_size: Option<PackageSize>,
+    /// True if the size grew by more than the configured threshold
+    pub over_threshold: bool,
+}
+
+impl SizeDiff {
+    /// Marks the diff if the size grew by more than the threshold. Added and removed crates are never marked
+    fn new(
+        name: &str,
+        from_version: Option<Version>,
+        to_version: Option<Version>,
+        from_size: Option<PackageSize>,
+        to_size: Option<PackageSize>,
+        threshold: Option<f64>,
+    ) -> Self {
+        let mut size_diff = Self {
+            name: name.into(),
+            from_version,
+            to_version,
+            from_size,
+            to_size,
+            over_threshold: false,
+        };
+        if let Some(threshold) = threshold
+            && let Some(growth) = size_diff.growth_percent()
+        {
+            size_diff.over_threshold = growth > threshold;
+        }
+        size_diff
+    }
+
+    /// Size growth in percents. `None` for the added and removed crates
+    pub fn growth_percent(&self) -> Option<f64> {
+        growth_percent(self.from_size.as_ref()?, self.to_size.as_ref()?)
+    }
+}
+
+/// Unpacked size changes of all crates and the total growth of the dependency graph
+#[derive(Debug, Clone, Default)]
+pub struct SizeReport {
+    pub diffs: Vec<SizeDiff>,
+    /// Total size of the initial versions of all changed crates
+    pub from_total: PackageSize,
+    /// Total size of the target versions of all changed crates
+    pub to_total: PackageSize,
+}
+
+impl SizeReport {
+    /// Adds the diff and its sizes to the totals
+    fn push(&mut self, size_diff: SizeDiff) {
+        if let Some(from_size) = &size_diff.from_size {
+            self.from_total.add(from_size);
+        }
+        if let Some(to_size) = &size_diff.to_size {
+            self.to_total.add(to_size);
+        }
+        self.diffs.push(size_diff);
+    }
+
+    pub fn growth_percent(&self) -> Option<f64> {
+        growth_percent(&self.from_total, &self.to_total)
+    }
+}
+
+/// Compares unpacked sizes of the changed crates
+pub struct SizeComparator<'a> {
+    registry_manager: &'a RegistryManager,
+    /// Growth in percents after which the crate is marked
+    threshold: Option<f64>,
+}
+
+impl<'a> SizeComparator<'a> {
+    /// Command line threshold takes precedence over the configured one
+    pub fn new(
+        registry_manager: &'a RegistryManager,
+        config: &Config,
+        threshold: Option<f64>,
+    ) -> Self {
+        Self {
+            registry_manager,
+            threshold: threshold.or(config.size_threshold),
+        }
+    }
+
+    /// Returns size changes of all updated, added and removed crates in the report
+    pub fn compare(&self, report: &DiffReport) -> SizeReport {
+        let mut size_report = SizeReport::default();
+
+        for diff in report.crate_diffs() {
+            let from_size = diff.from_version.as_ref().and_then(|version| {
+                self.get_package_size(
+                    &diff.name,
+                    version,
+                    diff.from_hash.as_deref(),
+                    &diff.registry,
+                )
+            });
+            let to_size = diff.to_version.as_ref().and_then(|version| {
+                self.get_package_size(&diff.name, version, diff.to_hash.as_deref(), &diff.registry)
+            });
+
+            size_report.push(SizeDiff::new(
+                &diff.name,
+                diff.from_version.clone(),
+                diff.to_version.clone(),
+                from_size,
+                to_size,
+                self.threshold,
+            ));
+        }
+
+        size_report
+    }
+
+    fn get_package_size(
+        &self,
+        crate_name: &str,
+        version: &Version,
+        hash: Option<&str>,
+        registry: &RegistrySource,
+    ) -> Option<PackageSize> {
+        let crate_path = self
+            .registry_manager
+            .fetch_crate(crate_name, version, hash, registry)?;
+        match Self::package_size(&crate_path, matches!(registry, RegistrySource::Git(_))) {
+            Ok(size) => Some(size),
+            Err(err) => {
+                eprintln!(
+                    "[ERROR] Cannot get size of the '{crate_name}@{version}' crate. Error: {err:#}"
+                );
+                None
+            }
+        }
+    }
+
+    fn package_size(crate_path: &Path, git_checkout: bool) -> anyhow::Result<PackageSize> {
+        let mut size = PackageSize::default();
+        for path in list_crate_files(crate_path, git_checkout)? {
+            size.files += 1;
+            size.bytes += std::fs::symlink_metadata(crate_path.join(path))?.len();
+        }
+        Ok(size)
+    }
+}
+
+fn growth_percent(from_size: &PackageSize, to_size: &PackageSize) -> Option<f64> {
+    if from_size.bytes == 0 {
+        return None;
+    }
+    Some((to_size.bytes as f64 - from_size.bytes as f64) * 100.0 / from_size.bytes as f64)
+}
+
+/// Formats size in bytes into a human readable string
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next_unit in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use crate::size_comparator::{PackageSize, SizeDiff, SizeReport, format_size};
+
+    fn size(bytes: u64) -> Option<PackageSize> {
+        Some(PackageSize { files: 2, bytes })
+    }
+
+    fn size_diff(
+        from_size: Option<PackageSize>,
+        to_size: Option<PackageSize>,
+        threshold: Option<f64>,
+    ) -> SizeDiff {
+        SizeDiff::new(
+            "foo",
+            from_size.map(|_| Version::new(1, 0, 0)),
+            to_size.map(|_| Version::new(1, 1, 0)),
+            from_size,
+            to_size,
+            threshold,
+        )
+    }
+
+    #[test]
+    fn test_size_diff() {
+        let diff = size_diff(size(200), size(300), Some(10.0));
+        assert_eq!(Some(50.0), diff.growth_percent());
+        assert!(diff.over_threshold);
+
+        let diff = size_diff(size(200), size(300), Some(50.0));
+        assert!(!diff.over_threshold);
+        let diff = size_diff(size(200), size(300), None);
+        assert!(!diff.over_threshold);
+
+        let diff = size_diff(size(200), size(100), Some(-90.0));
+        assert_eq!(Some(-50.0), diff.growth_percent());
+        assert!(diff.over_threshold);
+
+        // empty initial package has no growth percents
+        let diff = size_diff(size(0), size(100), Some(0.0));
+        assert_eq!(None, diff.growth_percent());
+        assert!(!diff.over_threshold);
+
+        // added and removed crates
+        let diff = size_diff(None, size(100), Some(0.0));
+        assert_eq!(None, diff.growth_percent());
+        assert!(!diff.over_threshold);
+        let diff = size_diff(size(100), None, Some(0.0));
+        assert_eq!(None, diff.growth_percent());
+        assert!(!diff.over_threshold);
+    }
+
+    #[test]
+    fn test_size_report() {
+        let mut size_report = SizeReport::default();
+        assert_eq!(None, size_report.growth_percent());
+
+        size_report.push(size_diff(size(200), size(300), None));
+        size_report.push(size_diff(None, size(100), None));
+        size_report.push(size_diff(size(200), None, None));
+        size_report.push(size_diff(size(100), size(0), None));
+
+        assert_eq!(4, size_report.diffs.len());
+        assert_eq!(
+            PackageSize {
+                files: 6,
+                bytes: 500
+            },
+            size_report.from_total
+        );
+        assert_eq!(
+            PackageSize {
+                files: 6,
+                bytes: 400
+            },
+            size_report.to_total
+        );
+        assert_eq!(Some(-20.0), size_report.growth_percent());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!("0 B", format_size(0));
+        assert_eq!("1023 B", format_size(1023));
+        assert_eq!("1.0 KiB", format_size(1024));
+        assert_eq!("1.5 KiB", format_size(1536));
+        assert_eq!("2.0 MiB", format_size(2 * 1024 * 1024));
+        assert_eq!("3.0 GiB", format_size(3 * 1024 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use semver::Version;
+use similar::{ChangeTag, TextDiff};
+
+use crate::config::Config;
+use crate::crate_files::list_crate_files;
+use crate::diff_report::DiffReport;
+use crate::path_filter::PathFilter;
+use crate::registry_manager::RegistryManager;
+use crate::registry_source::RegistrySource;
+
+/// Source file change kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl FileStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Added => "A",
+            Self::Removed => "D",
+            Self::Modified => "M",
+        }
+    }
+}
+
+/// Changes of the single source file
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    /// Relative file path in the crate
+    pub path: String,
+    pub status: FileStatus,
+    /// Number of added lines. Always 0 for binary files
+    pub added_lines: usize,
+    /// Number of removed lines. Always 0 for binary files
+    pub removed_lines: usize,
+    /// True if file is not a valid UTF-8 text
+    pub binary: bool,
+    /// Unified diff of the file if requested
+    pub unified_diff: Option<String>,
+}
+
+/// Source changes between 2 versions of the crate in the local registry
+#[derive(Debug, Clone)]
+pub struct SourceDiff {
+    /// Crate name
+    pub name: String,
+    /// Initial crate version
+    pub from_version: Version,
+    /// Target crate version
+    pub to_version: Version,
+    /// Changed files that passed the path filter
+    pub files: Vec<FileDiff>,
+}
+
+impl SourceDiff {
+    pub fn added_lines(&self) -> usize {
+        self.files.iter().map(|f| f.added_lines).sum()
+    }
+
+    pub fn removed_lines(&self) -> usize {
+        self.files.iter().map(|f| f.removed_lines).sum()
+    }
+}
+
+/// Compares sources of 2 versions of the updated crates
+pub struct SourceComparator<'a> {
+    registry_manager: &'a RegistryManager,
+    config: &'a Config,
+    /// Filter from the command line
+    filter: PathFilter,
+    /// Generate unified diffs of the changed files
+    unified_diff: bool,
+}
+
+impl<'a> SourceComparator<'a> {
+    pub fn new(
+        registry_manager: &'a RegistryManager,
+        config: &'a Config,
+        filter: PathFilter,
+        unified_diff: bool,
+    ) -> Self {
+        Self {
+            registry_manager,
+            config,
+            filter,
+            unified_diff,
+        }
+    }
+
+    /// Returns source changes of all updated crates in the report
+    pub fn compare(&self, report: &DiffReport) -> Vec<SourceDiff> {
+        let mut source_diffs = vec![];
+
+        for diff in report.crate_diffs() {
+            let (Some(from_version), Some(to_version)) = (&diff.from_version, &diff.to_version)
+            else {
+                continue;
+            };
+            let Some(from_path) = self.registry_manager.fetch_crate(
+                &diff.name,
+                from_version,
+                diff.from_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+            let Some(to_path) = self.registry_manager.fetch_crate(
+                &diff.name,
+                to_version,
+                diff.to_hash.as_deref(),
+                &diff.registry,
+            ) else {
+                continue;
+            };
+
+            let mut filter = self.config.crate_filter(&diff.name);
+            filter.merge(&self.filter);
+
+            let git_checkout = matches!(diff.registry, RegistrySource::Git(_));
+            match self.compare_crates(&from_path, &to_path, git_checkout, &filter) {
+                Ok(files) => source_diffs.push(SourceDiff {
+                    name: diff.name.clone(),
+                    from_version: from_version.clone(),
+                    to_version: to_version.clone(),
+                    files,
+                }),
+                Err(err) => {
+                    eprintln!(
+                        "[ERROR] Cannot compare sources of the '{}' crate. Error: {err:#}",
+                        diff.name
+                    );
+                }
+            }
+        }
+
+        source_diffs
+    }
+
+    fn compare_crates(
+        &self,
+        from_path: &Path,
+        to_path: &Path,
+        git_checkout: bool,
+        filter: &PathFilter,
+    ) -> anyhow::Result<Vec<FileDiff>> {
+        let from_files: BTreeSet<_> = list_crate_files(from_path, git_checkout)?
+            .into_iter()
+            .filter(|f| filter.matches(f))
+            .collect();
+        let to_files: BTreeSet<_> = list_crate_files(to_path, git_checkout)?
+            .into_iter()
+            .filter(|f| filter.matches(f))
+            .collect();
+
+        let mut file_diffs = vec![];
+        for path in from_files.union(&to_files) {
+            let from_data = if from_files.contains(path) {
+                Some(read_file(&from_path.join(path))?)
+            } else {
+                None
+            };
+            let to_data = if to_files.contains(path) {
+                Some(read_file(&to_path.join(path))?)
+            } else {
+                None
+            };
+
+            if let Some(file_diff) = self.compare_files(path, from_data, to_data) {
+                file_diffs.push(file_diff);
+            }
+        }
+
+        Ok(file_diffs)
+    }
+
+    fn compare_files(
+        &self,
+        path: &str,
+        from_data: Option<Vec<u8>>,
+        to_data: Option<Vec<u8>>,
+    ) -> Option<FileDiff> {
+        let status = match (&from_data, &to_data) {
+            (Some(from_data), Some(to_data)) if from_data == to_data => return None,
+            (Some(_), Some(_)) => FileStatus::Modified,
+            (Some(_), None) => FileStatus::Removed,
+            (None, Some(_)) => FileStatus::Added,
+            (None, None) => return None,
+        };
+
+        let from_text = from_data.map(String::from_utf8).transpose();
+        let to_text = to_data.map(String::from_utf8).transpose();
+        let (Ok(from_text), Ok(to_text)) = (from_text, to_text) else {
+            return Some(FileDiff {
+                path: path.into(),
+                status,
+                added_lines: 0,
+                removed_lines: 0,
+                binary: true,
+                unified_diff: None,
+            });
+        };
+        let from_text = from_text.unwrap_or_default();
+        let to_text = to_text.unwrap_or_default();
+
+        let text_diff = TextDiff::from_lines(&from_text, &to_text);
+        let mut added_lines = 0;
+        let mut removed_lines = 0;
+        for change in text_diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => added_lines += 1,
+                ChangeTag::Delete => removed_lines += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+
+        let unified_diff = if self.unified_diff {
+            Some(
+                text_diff
+                    .unified_diff()
+                    .header(&format!("a/{path}"), &format!("b/{path}"))
+                    .to_string(),
+            )
+        } else {
+            None
+        };
+
+        Some(FileDiff {
+            path: path.into(),
+            status,
+            added_lines,
+            removed_lines,
+            binary: false,
+            unified_diff,
+        })
+    }
+}
+
+/// Content of the file. Symbolic links are never followed, their targets are compared as text instead
+fn read_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    if std::fs::symlink_metadata(path)?.is_symlink() {
+        let target = std::fs::read_link(path)?;
+        return Ok(format!("-> {}", target.to_string_lossy()).into_bytes());
+    }
+    std::fs::read(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source_comparator::read_file;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_symlink() {
+        let crate_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(crate_dir.path().join("src")).unwrap();
+        std::fs::write(crate_dir.path().join("src/lib.rs"), "pub fn foo() {}").unwrap();
+        std::os::unix::fs::symlink("src", crate_dir.path().join("src-dir")).unwrap();
+        std::os::unix::fs::symlink("/etc/passwd", crate_dir.path().join("passwd")).unwrap();
+
+        assert_eq!(
+            b"pub fn foo() {}".to_vec(),
+            read_file(&crate_dir.path().join("src/lib.rs")).unwrap()
+        );
+        assert_eq!(
+            b"-> src".to_vec(),
+            read_file(&crate_dir.path().join("src-dir")).unwrap()
+        );
+        assert_eq!(
+            b"-> /etc/passwd".to_vec(),
+            read_file(&crate_dir.path().join("passwd")).unwrap()
+        );
+    }
+}
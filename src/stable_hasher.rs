@@ -0,0 +1,119 @@
+use std::hash::Hasher;
+
+/// SipHash-1-3 hasher with 128-bit output used by cargo to name its registry folders.
+/// Produces the same values on all platforms.
+#[derive(Debug, Clone)]
+pub struct StableHasher {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    /// Bytes that don't fill the whole 8-byte element yet
+    tail: Vec<u8>,
+    /// Total number of written bytes
+    length: usize,
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StableHasher {
+    pub fn new() -> Self {
+        Self::with_keys(0, 0)
+    }
+
+    fn with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v0: k0 ^ 0x736f6d6570736575,
+            // 128-bit output variant
+            v1: k1 ^ 0x646f72616e646f6d ^ 0xee,
+            v2: k0 ^ 0x6c7967656e657261,
+            v3: k1 ^ 0x7465646279746573,
+            tail: Vec::with_capacity(8),
+            length: 0,
+        }
+    }
+
+    /// Returns both halves of the 128-bit hash
+    fn finish128(&self) -> (u64, u64) {
+        let mut state = self.clone();
+        let mut last = [0u8; 8];
+        last[..state.tail.len()].copy_from_slice(&state.tail);
+        let b = ((self.length as u64 & 0xff) << 56) | u64::from_le_bytes(last);
+
+        state.compress(b);
+        state.v2 ^= 0xee;
+        state.rounds(3);
+        let h0 = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+        state.v1 ^= 0xdd;
+        state.rounds(3);
+        let h1 = state.v0 ^ state.v1 ^ state.v2 ^ state.v3;
+
+        (h0, h1)
+    }
+
+    fn compress(&mut self, m: u64) {
+        self.v3 ^= m;
+        self.rounds(1);
+        self.v0 ^= m;
+    }
+
+    fn rounds(&mut self, count: usize) {
+        for _ in 0..count {
+            self.v0 = self.v0.wrapping_add(self.v1);
+            self.v1 = self.v1.rotate_left(13) ^ self.v0;
+            self.v0 = self.v0.rotate_left(32);
+            self.v2 = self.v2.wrapping_add(self.v3);
+            self.v3 = self.v3.rotate_left(16) ^ self.v2;
+            self.v0 = self.v0.wrapping_add(self.v3);
+            self.v3 = self.v3.rotate_left(21) ^ self.v0;
+            self.v2 = self.v2.wrapping_add(self.v1);
+            self.v1 = self.v1.rotate_left(17) ^ self.v2;
+            self.v2 = self.v2.rotate_left(32);
+        }
+    }
+}
+
+impl Hasher for StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        self.length += bytes.len();
+        for byte in bytes {
+            self.tail.push(*byte);
+            if self.tail.len() == 8 {
+                let mut element = [0u8; 8];
+                element.copy_from_slice(&self.tail);
+                self.tail.clear();
+                self.compress(u64::from_le_bytes(element));
+            }
+        }
+    }
+
+    /// Combines both halves of the 128-bit hash
+    fn finish(&self) -> u64 {
+        let (h0, h1) = self.finish128();
+        h0.wrapping_mul(3).wrapping_add(h1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use crate::stable_hasher::StableHasher;
+
+    #[test]
+    fn test_siphash_1_3_128() {
+        let hasher = StableHasher::with_keys(0x0706050403020100, 0x0f0e0d0c0b0a0908);
+        assert_eq!((0xbea58827b2bc7ee7, 0x013030dd6adb62fd), hasher.finish128());
+
+        let mut hasher = StableHasher::with_keys(0x0706050403020100, 0x0f0e0d0c0b0a0908);
+        hasher.write(&[0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        let mut split_hasher = StableHasher::with_keys(0x0706050403020100, 0x0f0e0d0c0b0a0908);
+        split_hasher.write(&[0, 1, 2]);
+        split_hasher.write(&[3, 4, 5, 6, 7, 8]);
+        assert_eq!(hasher.finish(), split_hasher.finish());
+    }
+}
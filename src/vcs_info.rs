@@ -0,0 +1,69 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// File with the version control information that cargo adds to the published crates
+pub const VCS_INFO_FILE_NAME: &str = ".cargo_vcs_info.json";
+
+/// Version control information of the published crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VcsInfo {
+    /// Commit hash the crate was published from
+    pub hash: String,
+    /// Crate folder inside the repository. `None` for the crates in the repository root
+    pub path_in_vcs: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VcsInfoFile {
+    git: GitInfo,
+    #[serde(default)]
+    path_in_vcs: String,
+}
+
+#[derive(Deserialize)]
+struct GitInfo {
+    sha1: String,
+}
+
+impl VcsInfo {
+    /// Parses content of the `.cargo_vcs_info.json` file
+    pub fn parse(data: &str) -> Result<Self> {
+        let file: VcsInfoFile = serde_json::from_str(data)?;
+        Ok(Self {
+            hash: file.git.sha1,
+            path_in_vcs: Some(file.path_in_vcs).filter(|path| !path.is_empty()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vcs_info::VcsInfo;
+
+    #[test]
+    fn test_parse_vcs_info() {
+        assert_eq!(
+            VcsInfo {
+                hash: "1d7899d671c6f6155b63a39fa6001c9c48260821".into(),
+                path_in_vcs: Some("serde_derive".into()),
+            },
+            VcsInfo::parse(
+                r#"{
+  "git": {
+    "sha1": "1d7899d671c6f6155b63a39fa6001c9c48260821"
+  },
+  "path_in_vcs": "serde_derive"
+}"#
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            VcsInfo {
+                hash: "abc".into(),
+                path_in_vcs: None,
+            },
+            VcsInfo::parse(r#"{"git":{"sha1":"abc","dirty":true},"path_in_vcs":""}"#).unwrap()
+        );
+        assert!(VcsInfo::parse(r#"{"path_in_vcs":""}"#).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use semver::Version;
+
+use crate::crate_archive::{ChecksumMismatch, ChecksumSource};
+use crate::crate_manifest::CrateManifest;
+use crate::repository_url::RepositoryUrl;
+
+/// File with the checksums of the vendored crate files and the whole package
+const CHECKSUM_FILE_NAME: &str = ".cargo-checksum.json";
+
+/// Crate unpacked by `cargo vendor`
+#[derive(Debug, Clone)]
+pub struct VendorCrate {
+    /// Crate name
+    pub name: String,
+    /// Crate version
+    pub version: Version,
+    /// Path to the crate sources
+    pub path: PathBuf,
+    /// Repository from the crate manifest
+    pub repository: Option<String>,
+    /// Crate folder from the `tree`/`blob` repository link
+    pub repository_subpath: Option<String>,
+    /// Homepage from the crate manifest
+    pub homepage: Option<String>,
+    /// SHA-256 checksum of the `.crate` package from the `.cargo-checksum.json` file
+    pub checksum: Option<String>,
+}
+
+/// Directory with the crates vendored by `cargo vendor`
+#[derive(Debug, Clone)]
+pub struct VendorDir {
+    crates: Vec<VendorCrate>,
+}
+
+impl VendorDir {
+    /// Reads all crates of the vendor directory. Folders without manifest are skipped.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.is_dir() {
+            return Err(anyhow!("Vendor directory doesn't exist: {path:?}"));
+        }
+
+        let mut crates = vec![];
+        for entry in std::fs::read_dir(path)
+            .with_context(|| format!("Cannot read vendor directory: {path:?}"))?
+        {
+            let crate_path = entry?.path();
+            if !crate_path.join("Cargo.toml").is_file() {
+                continue;
+            }
+            match Self::read_crate(&crate_path) {
+                Ok(vendor_crate) => crates.push(vendor_crate),
+                Err(err) => {
+                    eprintln!("[WARN] Cannot read vendored crate {crate_path:?}. Error: {err:#}");
+                }
+            }
+        }
+        crates.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+
+        Ok(Self { crates })
+    }
+
+    /// Vendored crates sorted by name and version
+    pub fn crates(&self) -> &[VendorCrate] {
+        &self.crates
+    }
+
+    pub fn find(&self, crate_name: &str, version: &Version) -> Option<&VendorCrate> {
+        self.crates
+            .iter()
+            .find(|c| c.name == crate_name && c.version == *version)
+    }
+
+    /// Crate versions of the candidate directory whose package checksums differ from the same versions in this directory.
+    /// The vendored code of the same version is replaced
+    pub fn checksum_mismatches(&self, candidate_dir: &VendorDir) -> Vec<ChecksumMismatch> {
+        candidate_dir
+            .crates
+            .iter()
+            .filter_map(|candidate| {
+                let current = self.find(&candidate.name, &candidate.version)?;
+                (current.checksum != candidate.checksum).then(|| ChecksumMismatch {
+                    name: candidate.name.clone(),
+                    version: candidate.version.clone(),
+                    source: ChecksumSource::Vendor,
+                    expected: current.checksum.clone().unwrap_or_default(),
+                    actual: candidate.checksum.clone().unwrap_or_default(),
+                    archive: candidate.path.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Reads only the versions of the crate: `<path>/<name>` and `<path>/<name>-<version>` folders
+    pub fn read_crate_versions(path: &Path, crate_name: &str) -> Result<Vec<VendorCrate>> {
+        let prefix = format!("{crate_name}-");
+        let mut crates = vec![];
+        for entry in std::fs::read_dir(path)
+            .with_context(|| format!("Cannot read vendor directory: {path:?}"))?
+        {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let Some(file_name) = file_name.to_str() else {
+                continue;
+            };
+            if file_name != crate_name && !file_name.starts_with(&prefix) {
+                continue;
+            }
+            let crate_path = entry.path();
+            if !crate_path.join("Cargo.toml").is_file() {
+                continue;
+            }
+            match Self::read_crate(&crate_path) {
+                // `foo-bar` folder may contain `foo-bar` crate instead of the `foo` one
+                Ok(vendor_crate) if vendor_crate.name == crate_name => crates.push(vendor_crate),
+                Ok(_) => {}
+                Err(err) => {
+                    eprintln!("[WARN] Cannot read vendored crate {crate_path:?}. Error: {err:#}");
+                }
+            }
+        }
+        crates.sort_by(|a, b| a.version.cmp(&b.version));
+        Ok(crates)
+    }
+
+    fn read_crate(crate_path: &Path) -> Result<VendorCrate> {
+        let manifest = CrateManifest::read(crate_path)?;
+        let name = manifest
+            .package
+            .name
+            .ok_or_else(|| anyhow!("Crate name is not specified"))?;
+        let version = manifest
+            .package
+            .version
+            .ok_or_else(|| anyhow!("Crate version is not specified"))?;
+        let repository_url = manifest
+            .package
+            .repository
+            .as_deref()
+            .map(RepositoryUrl::parse);
+
+        Ok(VendorCrate {
+            name,
+            version: Version::parse(&version)?,
+            path: crate_path.into(),
+            repository: repository_url.as_ref().map(|url| url.url.clone()),
+            repository_subpath: repository_url.and_then(|url| url.subpath),
+            homepage: manifest.package.homepage,
+            checksum: read_package_checksum(crate_path),
+        })
+    }
+}
+
+/// Reads package checksum from the `.cargo-checksum.json` file of the vendored crate
+fn read_package_checksum(crate_path: &Path) -> Option<String> {
+    let checksum_data = std::fs::read_to_string(crate_path.join(CHECKSUM_FILE_NAME)).ok()?;
+
+    // Do not parse Json. Just read the value of the "package" field
+    let (_, checksum) = checksum_data.split_once("\"package\":")?;
+    let checksum = checksum.trim_start().strip_prefix('"')?;
+    let (checksum, _) = checksum.split_once('"')?;
+    Some(checksum.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use semver::Version;
+
+    use crate::crate_archive::ChecksumSource;
+    use crate::vendor_dir::VendorDir;
+
+    fn write_vendor_crate(vendor_path: &Path, dir: &str, version: &str, checksum: &str) {
+        let crate_path = vendor_path.join(dir);
+        std::fs::create_dir_all(&crate_path).unwrap();
+        std::fs::write(
+            crate_path.join("Cargo.toml"),
+            format!("[package]\nname = \"foo\"\nversion = \"{version}\"\n"),
+        )
+        .unwrap();
+        std::fs::write(
+            crate_path.join(".cargo-checksum.json"),
+            format!("{{\"files\":{{}},\"package\":\"{checksum}\"}}"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_read_vendor_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let vendor_path = temp_dir.path().join("vendor");
+        for (dir, version) in [("foo", "1.2.0"), ("foo-0.9.1", "0.9.1")] {
+            let crate_path = vendor_path.join(dir);
+            std::fs::create_dir_all(&crate_path).unwrap();
+            std::fs::write(
+                crate_path.join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"foo\"\nversion = \"{version}\"\nrepository = \"https://github.com/foo/foo/\"\n"
+                ),
+            )
+            .unwrap();
+            std::fs::write(
+                crate_path.join(".cargo-checksum.json"),
+                format!("{{\"files\":{{\"src/lib.rs\":\"00\"}},\"package\":\"abc{version}\"}}"),
+            )
+            .unwrap();
+        }
+        std::fs::create_dir_all(vendor_path.join("not-a-crate")).unwrap();
+
+        let vendor_dir = VendorDir::read(&vendor_path).unwrap();
+        assert_eq!(2, vendor_dir.crates().len());
+        assert_eq!(Version::new(0, 9, 1), vendor_dir.crates()[0].version);
+
+        let foo = vendor_dir.find("foo", &Version::new(1, 2, 0)).unwrap();
+        assert_eq!(Some("abc1.2.0"), foo.checksum.as_deref());
+        assert_eq!(
+            Some("https://github.com/foo/foo"),
+            foo.repository.as_deref()
+        );
+    }
+
+    #[test]
+    fn test_checksum_mismatches() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let current_path = temp_dir.path().join("current");
+        let candidate_path = temp_dir.path().join("candidate");
+        write_vendor_crate(&current_path, "foo-1.0.0", "1.0.0", "aa");
+        write_vendor_crate(&current_path, "foo", "1.1.0", "bb");
+        write_vendor_crate(&candidate_path, "foo-1.0.0", "1.0.0", "aa");
+        write_vendor_crate(&candidate_path, "foo-1.1.0", "1.1.0", "cc");
+        write_vendor_crate(&candidate_path, "foo", "1.2.0", "dd");
+
+        let current_dir = VendorDir::read(&current_path).unwrap();
+        let candidate_dir = VendorDir::read(&candidate_path).unwrap();
+        let mismatches = current_dir.checksum_mismatches(&candidate_dir);
+
+        assert_eq!(1, mismatches.len());
+        assert_eq!(Version::new(1, 1, 0), mismatches[0].version);
+        assert_eq!(ChecksumSource::Vendor, mismatches[0].source);
+        assert_eq!("bb", mismatches[0].expected);
+        assert_eq!("cc", mismatches[0].actual);
+        assert!(mismatches[0].archive.ends_with("foo-1.1.0"));
+    }
+}
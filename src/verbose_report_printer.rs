@@ -1,17 +1,32 @@
+use std::cmp::Ordering;
 use std::collections::BTreeSet;
 
+use crate::api_comparator::{ApiDiff, ApiItem};
+use crate::crate_archive::{ChecksumMismatch, ChecksumSource};
 use crate::crate_diff_info::CrateDiffInfo;
 use crate::dependency_diff::DependencyDiff;
 use crate::diff_report::DiffReport;
+use crate::forge::ForgeLinks;
+use crate::license_checker::LicenseDiff;
+use crate::package_inspector::PackageAnomalies;
+use crate::registry_source::RegistrySource;
+use crate::repository_checker::RepositoryChange;
+use crate::size_comparator::{PackageSize, SizeDiff, SizeReport, format_size};
+use crate::source_comparator::SourceDiff;
 
 pub struct VerboseReportPrinter {
     group: bool,
     diff_rs: bool,
+    forge_links: ForgeLinks,
 }
 
 impl VerboseReportPrinter {
-    pub fn new(group: bool, diff_rs: bool) -> Self {
-        Self { group, diff_rs }
+    pub fn new(group: bool, diff_rs: bool, forge_links: ForgeLinks) -> Self {
+        Self {
+            group,
+            diff_rs,
+            forge_links,
+        }
     }
 
     pub fn print(&self, report: &DiffReport) {
@@ -30,6 +45,52 @@ impl VerboseReportPrinter {
                 self.print_diffs(diffs);
             }
         }
+
+        if !report.checksum_mismatches.is_empty() {
+            println!("Checksum mismatches (critical):");
+            for mismatch in &report.checksum_mismatches {
+                self.print_checksum_mismatch(mismatch);
+            }
+        }
+
+        if !report.repository_changes.is_empty() {
+            println!("Repository changes:");
+            for change in &report.repository_changes {
+                self.print_repository_change(change);
+            }
+        }
+
+        if !report.license_diffs.is_empty() {
+            println!("License changes:");
+            for diff in &report.license_diffs {
+                self.print_license_diff(diff);
+            }
+        }
+
+        if !report.source_diffs.is_empty() {
+            println!("Source changes:");
+            for diff in &report.source_diffs {
+                self.print_source_diff(diff);
+            }
+        }
+
+        if !report.api_diffs.is_empty() {
+            println!("Public API changes:");
+            for diff in &report.api_diffs {
+                self.print_api_diff(diff);
+            }
+        }
+
+        if !report.package_anomalies.is_empty() {
+            println!("Package anomalies:");
+            for anomalies in &report.package_anomalies {
+                self.print_package_anomalies(anomalies);
+            }
+        }
+
+        if let Some(size_report) = &report.size_report {
+            self.print_size_report(size_report);
+        }
     }
 
     fn print_grouped_diff(&self, diff: &DependencyDiff) {
@@ -93,28 +154,34 @@ impl VerboseReportPrinter {
     fn print_crate_diff(&self, diff: &CrateDiffInfo, indent: usize) {
         let ident_str = " ".repeat(indent * 2);
         print!("{ident_str}{}:", diff.name);
+        // diff.rs knows only the published crates
+        let diff_rs = self.diff_rs && !matches!(diff.registry, RegistrySource::Git(_));
 
         if let Some(from_version) = &diff.from_version {
             if let Some(to_version) = &diff.to_version {
                 // updated dependency
-                let version_change = if from_version < to_version {
-                    "upgraded"
-                } else {
-                    "downgraded"
+                let version_change = match from_version.cmp(to_version) {
+                    Ordering::Less => "upgraded",
+                    Ordering::Greater => "downgraded",
+                    // git crate with the new commit
+                    Ordering::Equal => "updated",
                 };
 
                 println!("{version_change}");
-                if let Some(repository) = &diff.repository
-                    && repository.starts_with("https://github.com/")
-                    && let (Some(from_hash), Some(to_hash)) = (&diff.from_hash, &diff.to_hash)
+                let from_link = self.forge_links.commit_link(
+                    diff,
+                    diff.from_hash.as_deref(),
+                    Some(from_version),
+                );
+                let to_link =
+                    self.forge_links
+                        .commit_link(diff, diff.to_hash.as_deref(), Some(to_version));
+                if let (Some(from_link), Some(to_link), Some(compare_link)) =
+                    (from_link, to_link, self.forge_links.compare_link(diff))
                 {
-                    println!("{ident_str}  From: {from_version} {repository}/commit/{from_hash}");
-                    println!("{ident_str}  To:   {to_version} {repository}/commit/{to_hash}");
-                    println!(
-                        "{ident_str}  Diff: {repository}/compare/{}...{}",
-                        &from_hash[..7],
-                        &to_hash[..7]
-                    );
+                    println!("{ident_str}  From: {from_version} {from_link}");
+                    println!("{ident_str}  To:   {to_version} {to_link}");
+                    println!("{ident_str}  Diff: {compare_link}");
                 } else {
                     println!(
                         "{ident_str}  From: {from_version} {}",
@@ -130,7 +197,7 @@ impl VerboseReportPrinter {
                         diff.repository.as_deref().unwrap_or("<unknown-repository>")
                     );
                 }
-                if self.diff_rs {
+                if diff_rs {
                     println!(
                         "{ident_str}  Diff: https://diff.rs/{}/{from_version}/{to_version}",
                         diff.name,
@@ -144,11 +211,12 @@ impl VerboseReportPrinter {
                 );
                 print!("{ident_str}  Repo: ");
 
-                if let Some(repository) = &diff.repository
-                    && repository.starts_with("https://github.com/")
-                    && let Some(from_hash) = &diff.from_hash
-                {
-                    println!("{repository}/commit/{from_hash}");
+                if let Some(link) = self.forge_links.commit_link(
+                    diff,
+                    diff.from_hash.as_deref(),
+                    Some(from_version),
+                ) {
+                    println!("{link}");
                 } else {
                     println!(
                         "{} {}",
@@ -156,7 +224,7 @@ impl VerboseReportPrinter {
                         diff.from_hash.as_deref().unwrap_or("<unknown-commit>"),
                     );
                 }
-                if self.diff_rs {
+                if diff_rs {
                     println!(
                         "{ident_str}  Diff: https://diff.rs/{}/{from_version}/{from_version}",
                         diff.name
@@ -171,11 +239,11 @@ impl VerboseReportPrinter {
             );
             print!("{ident_str}  Repo: ");
 
-            if let Some(repository) = &diff.repository
-                && repository.starts_with("https://github.com/")
-                && let Some(to_hash) = &diff.to_hash
+            if let Some(link) =
+                self.forge_links
+                    .commit_link(diff, diff.to_hash.as_deref(), Some(to_version))
             {
-                println!("{repository}/commit/{to_hash}");
+                println!("{link}");
             } else {
                 println!(
                     "{} {}",
@@ -183,7 +251,7 @@ impl VerboseReportPrinter {
                     diff.to_hash.as_deref().unwrap_or("<unknown-commit>"),
                 );
             }
-            if self.diff_rs {
+            if diff_rs {
                 println!(
                     "{ident_str}  Diff: https://diff.rs/{}/{to_version}/{to_version}",
                     diff.name
@@ -196,5 +264,209 @@ impl VerboseReportPrinter {
                 diff.repository.as_deref().unwrap_or("<unknown-repository>")
             );
         }
+
+        let yanked_versions = diff.yanked_versions();
+        if !yanked_versions.is_empty() {
+            let yanked_versions: Vec<_> = yanked_versions.iter().map(|v| v.to_string()).collect();
+            println!("{ident_str}  Yanked: {}", yanked_versions.join(", "));
+        }
+    }
+
+    fn print_repository_change(&self, change: &RepositoryChange) {
+        println!(
+            "  {}: {} -> {} ({} severity)",
+            change.name,
+            change.from_version,
+            change.to_version,
+            change.severity()
+        );
+        println!(
+            "    Repository: {} -> {}",
+            change
+                .from_repository
+                .as_deref()
+                .unwrap_or("<no-repository>"),
+            change.to_repository.as_deref().unwrap_or("<no-repository>")
+        );
+        if change.owner_changed {
+            println!("    Repository owner is changed");
+        } else if change.to_repository.is_none() {
+            println!("    Repository is removed");
+        } else if change.from_repository.is_none() {
+            println!("    Repository is added");
+        }
+        if change.from_homepage != change.to_homepage {
+            println!(
+                "    Homepage: {} -> {}",
+                change
+                    .from_homepage
+                    .as_deref()
+                    .unwrap_or("<unknown-homepage>"),
+                change
+                    .to_homepage
+                    .as_deref()
+                    .unwrap_or("<unknown-homepage>")
+            );
+        }
+    }
+
+    fn print_license_diff(&self, diff: &LicenseDiff) {
+        let to_license = diff.to_license.as_deref().unwrap_or("<unknown-license>");
+
+        if let Some(from_version) = &diff.from_version {
+            let from_license = diff.from_license.as_deref().unwrap_or("<unknown-license>");
+            println!("  {}: {from_version} -> {}", diff.name, diff.to_version);
+            if diff.from_license == diff.to_license {
+                println!("    License: {to_license}");
+            } else {
+                println!("    License: {from_license} -> {to_license}");
+            }
+            if !diff.changed_files.is_empty() {
+                println!("    Changed files: {}", diff.changed_files.join(", "));
+            }
+        } else {
+            println!("  {}: added {}", diff.name, diff.to_version);
+            println!("    License: {to_license}");
+            if !diff.changed_files.is_empty() {
+                println!("    Files: {}", diff.changed_files.join(", "));
+            }
+        }
+
+        if !diff.allowed {
+            println!("    License is not allowed");
+        }
     }
+
+    fn print_source_diff(&self, diff: &SourceDiff) {
+        println!(
+            "  {}: {} -> {} ({} files changed, +{} -{})",
+            diff.name,
+            diff.from_version,
+            diff.to_version,
+            diff.files.len(),
+            diff.added_lines(),
+            diff.removed_lines(),
+        );
+
+        for file in &diff.files {
+            if file.binary {
+                println!("    {} {} (binary)", file.status.as_str(), file.path);
+            } else {
+                println!(
+                    "    {} {} +{} -{}",
+                    file.status.as_str(),
+                    file.path,
+                    file.added_lines,
+                    file.removed_lines
+                );
+            }
+            if let Some(unified_diff) = &file.unified_diff {
+                print!("{unified_diff}");
+            }
+        }
+    }
+
+    fn print_api_diff(&self, diff: &ApiDiff) {
+        println!(
+            "  {}: {} -> {}",
+            diff.name, diff.from_version, diff.to_version
+        );
+        if diff.semver_violation {
+            println!("    Suspected semver violation: public items removed in non-breaking update");
+        }
+
+        self.print_api_items(&diff.added, "Added");
+        self.print_api_items(&diff.removed, "Removed");
+
+        if !diff.changed.is_empty() {
+            println!("    Changed:");
+            for (from_item, to_item) in &diff.changed {
+                println!("      {} {}", to_item.kind, to_item.path);
+                println!("        - {}", from_item.signature);
+                println!("        + {}", to_item.signature);
+            }
+        }
+    }
+
+    fn print_api_items(&self, items: &[ApiItem], name: &str) {
+        if !items.is_empty() {
+            println!("    {name}:");
+            for item in items {
+                println!("      {} {}", item.kind, item.path);
+            }
+        }
+    }
+
+    fn print_checksum_mismatch(&self, mismatch: &ChecksumMismatch) {
+        println!("  {}: {}", mismatch.name, mismatch.version);
+        if mismatch.source == ChecksumSource::Vendor {
+            println!("    candidate crate: {}", mismatch.archive.display());
+        } else {
+            println!("    archive: {}", mismatch.archive.display());
+        }
+        if mismatch.source == ChecksumSource::Missing {
+            println!("    no checksum to verify against");
+        } else {
+            println!(
+                "    {} checksum: {}",
+                mismatch.source.as_str(),
+                mismatch.expected
+            );
+        }
+        println!("    actual checksum: {}", mismatch.actual);
+    }
+
+    fn print_package_anomalies(&self, anomalies: &PackageAnomalies) {
+        println!("  {}: {}", anomalies.name, anomalies.version);
+        for anomaly in &anomalies.anomalies {
+            println!("    {}: {}", anomaly.kind(), anomaly.path());
+        }
+    }
+
+    fn print_size_report(&self, size_report: &SizeReport) {
+        println!("Package sizes:");
+        for diff in &size_report.diffs {
+            self.print_size_diff(diff);
+        }
+
+        println!("  Total:");
+        println!(
+            "    From: {}",
+            format_package_size(Some(&size_report.from_total))
+        );
+        println!(
+            "    To:   {}",
+            format_package_size(Some(&size_report.to_total))
+        );
+        if let Some(growth) = size_report.growth_percent() {
+            println!("    Growth: {growth:+.1}%");
+        }
+    }
+
+    fn print_size_diff(&self, diff: &SizeDiff) {
+        println!("  {}:", diff.name);
+        if let Some(from_version) = &diff.from_version {
+            println!(
+                "    From: {from_version} {}",
+                format_package_size(diff.from_size.as_ref())
+            );
+        }
+        if let Some(to_version) = &diff.to_version {
+            println!(
+                "    To:   {to_version} {}",
+                format_package_size(diff.to_size.as_ref())
+            );
+        }
+        if let Some(growth) = diff.growth_percent() {
+            println!("    Growth: {growth:+.1}%");
+        }
+        if diff.over_threshold {
+            println!("    Size growth is over the threshold");
+        }
+    }
+}
+
+fn format_package_size(size: Option<&PackageSize>) -> String {
+    size.map(|s| format!("{} in {} files", format_size(s.bytes), s.files))
+        .unwrap_or("<unknown-size>".into())
 }
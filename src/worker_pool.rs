@@ -0,0 +1,135 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bounded pool of the worker threads shared by the nested parallel maps.
+/// The calling thread always takes part in the work so the nested maps never wait for the free workers.
+pub struct WorkerPool {
+    /// Maximum number of the threads working at the same time including the calling one
+    jobs: usize,
+    /// Number of the spawned worker threads that are currently running
+    workers: AtomicUsize,
+}
+
+impl WorkerPool {
+    pub fn new(jobs: usize) -> Self {
+        Self {
+            jobs: jobs.max(1),
+            workers: AtomicUsize::new(0),
+        }
+    }
+
+    /// Default number of jobs: number of the available CPUs
+    pub fn default_jobs() -> usize {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    }
+
+    /// Applies `f` to all items in parallel. Results have the same order as the items
+    pub fn map<T, R, F>(&self, items: Vec<T>, f: F) -> Vec<R>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> R + Sync,
+    {
+        let count = items.len();
+        let workers = self.reserve_workers(count.saturating_sub(1));
+        if workers == 0 {
+            return items.into_iter().map(f).collect();
+        }
+
+        let items: Vec<_> = items
+            .into_iter()
+            .map(|item| Mutex::new(Some(item)))
+            .collect();
+        let results: Vec<_> = (0..count).map(|_| Mutex::new(None)).collect();
+        let next = AtomicUsize::new(0);
+        let work = || {
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= count {
+                    break;
+                }
+                let item = lock(&items[index]).take().expect("Item is taken only once");
+                let result = f(item);
+                *lock(&results[index]) = Some(result);
+            }
+        };
+
+        std::thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    work();
+                    self.workers.fetch_sub(1, Ordering::Relaxed);
+                });
+            }
+            work();
+        });
+
+        results
+            .into_iter()
+            .map(|result| {
+                result
+                    .into_inner()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .expect("All items are processed")
+            })
+            .collect()
+    }
+
+    /// Reserves up to `wanted` worker threads that fit into the jobs limit
+    fn reserve_workers(&self, wanted: usize) -> usize {
+        let max_workers = self.jobs - 1;
+        let mut current = self.workers.load(Ordering::Relaxed);
+        loop {
+            let reserved = wanted.min(max_workers.saturating_sub(current));
+            if reserved == 0 {
+                return 0;
+            }
+            match self.workers.compare_exchange(
+                current,
+                current + reserved,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return reserved,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::worker_pool::WorkerPool;
+
+    #[test]
+    fn test_worker_pool_map() {
+        let pool = WorkerPool::new(4);
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        let results = pool.map((0..20).collect(), |i: usize| {
+            // nested maps share the same jobs limit
+            let nested = pool.map((0..5).collect(), |j: usize| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                running.fetch_sub(1, Ordering::SeqCst);
+                i * 10 + j
+            });
+            nested.into_iter().sum::<usize>()
+        });
+
+        let expected: Vec<_> = (0..20).map(|i| i * 50 + 10).collect();
+        assert_eq!(expected, results);
+        assert!(max_running.load(Ordering::SeqCst) <= 4);
+        assert_eq!(0, pool.workers.load(Ordering::SeqCst));
+    }
+}
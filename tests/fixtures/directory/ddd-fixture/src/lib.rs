@@ -0,0 +1,7 @@
+pub fn answer() -> u32 {
+    42
+}
+
+pub fn question() -> &'static str {
+    "unknown"
+}
@@ -0,0 +1,284 @@
+//! Runs `cargo ddd` against the fixture local registry and directory sources that replace crates.io.
+//! Fixture crate `ddd-fixture` has versions 1.0.0, 1.1.0, yanked 1.2.0 and pre-release 2.0.0-rc.1.
+//! The directory source contains only 1.0.0 and 1.1.0.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// crates.io sparse index folder name in the cargo registry
+const CRATES_IO_DIR: &str = "index.crates.io-1949cf8c6b5b557f";
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()));
+        } else {
+            std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+        }
+    }
+}
+
+/// Folder whose cargo configuration replaces crates.io with the source
+fn source_work_dir(source_kind: &str, source_name: &str) -> tempfile::TempDir {
+    let work_dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(work_dir.path().join(".cargo")).unwrap();
+    std::fs::write(
+        work_dir.path().join(".cargo").join("config.toml"),
+        format!(
+            "[source.crates-io]\nreplace-with = \"fixture\"\n\n[source.fixture]\n{source_kind} = '{}'\n",
+            fixture_path(source_name).display()
+        ),
+    )
+    .unwrap();
+    work_dir
+}
+
+/// Runs `cargo ddd --offline --no-cache <args>` in the folder whose cargo configuration replaces crates.io with the source
+fn run_ddd(source_kind: &str, source_name: &str, args: &[&str]) -> (bool, String) {
+    let work_dir = source_work_dir(source_kind, source_name);
+    run_ddd_in(work_dir.path(), args)
+}
+
+/// Runs `cargo ddd --offline --no-cache <args>` in the folder with the `cargo-home` cargo home
+fn run_ddd_in(work_dir: &Path, args: &[&str]) -> (bool, String) {
+    let cargo_home = work_dir.join("cargo-home");
+    std::fs::create_dir_all(&cargo_home).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-ddd"))
+        .current_dir(work_dir)
+        .env("CARGO_HOME", &cargo_home)
+        .args(["ddd", "--offline", "--no-cache"])
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stdout).unwrap(),
+    )
+}
+
+#[test]
+fn test_local_registry_latest_version() {
+    let (success, output) = run_ddd("local-registry", "local-registry", &["ddd-fixture@1.0.0-"]);
+    assert!(success, "{output}");
+    assert_eq!(
+        "# ddd-fixture 1.0.0 1.1.0 https://github.com/example/ddd-fixture/compare/1000000...1100000\n",
+        output
+    );
+
+    let (success, output) = run_ddd(
+        "local-registry",
+        "local-registry",
+        &["--pre", "ddd-fixture@1.0.0-"],
+    );
+    assert!(success, "{output}");
+    assert!(
+        output.starts_with("# ddd-fixture 1.0.0 2.0.0-rc.1 "),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_local_registry_yanked_version() {
+    let (success, output) = run_ddd(
+        "local-registry",
+        "local-registry",
+        &["ddd-fixture@1.0.0-1.2.0"],
+    );
+    assert!(success, "{output}");
+    assert!(
+        output.contains("Y ddd-fixture 1.0.0 1.2.0 target version is yanked"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_local_registry_checksums_and_sources() {
+    let (success, output) = run_ddd(
+        "local-registry",
+        "local-registry",
+        &["--verify-checksums", "-s", "ddd-fixture@1.0.0-1.1.0"],
+    );
+    assert!(success, "{output}");
+    assert!(!output.contains("\nC "), "{output}");
+    assert!(
+        output.contains("S ddd-fixture 1.0.0 1.1.0 3 files changed, +6 -2"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_directory_latest_version() {
+    let (success, output) = run_ddd("directory", "directory", &["ddd-fixture@1.0.0-"]);
+    assert!(success, "{output}");
+    assert_eq!(
+        "# ddd-fixture 1.0.0 1.1.0 https://github.com/example/ddd-fixture/compare/1000000...1100000\n",
+        output
+    );
+}
+
+#[test]
+fn test_missing_checksum() {
+    // crates are in the local cargo cache, but there is no index entry with the checksums
+    let work_dir = tempfile::tempdir().unwrap();
+    let registry_path = work_dir.path().join("cargo-home").join("registry");
+    for version in ["1.0.0", "1.1.0"] {
+        let crate_dir = format!("ddd-fixture-{version}");
+        let cache_path = registry_path.join("cache").join(CRATES_IO_DIR);
+        std::fs::create_dir_all(&cache_path).unwrap();
+        std::fs::copy(
+            fixture_path("local-registry").join(format!("{crate_dir}.crate")),
+            cache_path.join(format!("{crate_dir}.crate")),
+        )
+        .unwrap();
+    }
+    let src_path = registry_path.join("src").join(CRATES_IO_DIR);
+    copy_dir(
+        &fixture_path("directory").join("ddd-fixture-1.0.0"),
+        &src_path.join("ddd-fixture-1.0.0"),
+    );
+    copy_dir(
+        &fixture_path("directory").join("ddd-fixture"),
+        &src_path.join("ddd-fixture-1.1.0"),
+    );
+
+    let (success, output) = run_ddd_in(
+        work_dir.path(),
+        &["--verify-checksums", "ddd-fixture@1.0.0-1.1.0"],
+    );
+    assert!(!success, "{output}");
+    assert!(
+        output.contains("C ddd-fixture 1.1.0 no checksum to verify against"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_diff_rs_repository_change() {
+    // diff.rs links still need repositories of both versions to check them
+    let (success, output) = run_ddd(
+        "local-registry",
+        "local-registry",
+        &["-d", "ddd-fixture@1.0.0-1.1.0"],
+    );
+    assert!(success, "{output}");
+    assert!(output.starts_with("# ddd-fixture 1.0.0 1.1.0 "), "{output}");
+    assert!(!output.contains("\nR "), "{output}");
+}
+
+#[test]
+fn test_vendored_checksum_mismatch() {
+    // the same crate version is vendored with another package checksum in the candidate tree
+    let work_dir = tempfile::tempdir().unwrap();
+    copy_dir(&fixture_path("directory"), &work_dir.path().join("current"));
+    copy_dir(
+        &fixture_path("directory"),
+        &work_dir.path().join("candidate"),
+    );
+    let checksum_path = work_dir
+        .path()
+        .join("candidate")
+        .join("ddd-fixture")
+        .join(".cargo-checksum.json");
+    let checksum_file = std::fs::read_to_string(&checksum_path).unwrap();
+    std::fs::write(
+        &checksum_path,
+        checksum_file.replace("\"package\":\"", "\"package\":\"00"),
+    )
+    .unwrap();
+
+    let (success, output) = run_ddd_in(
+        work_dir.path(),
+        &[
+            "--vendor-dir",
+            "current",
+            "--candidate-vendor-dir",
+            "candidate",
+        ],
+    );
+    assert!(!success, "{output}");
+    assert!(
+        output.contains("C ddd-fixture 1.1.0 current vendor directory: expected "),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_local_registry_size() {
+    let (success, output) = run_ddd(
+        "local-registry",
+        "local-registry",
+        &[
+            "--size",
+            "ddd-fixture@1.0.0-1.1.0",
+            "ddd-fixture@1.1.0-2.0.0-rc.1",
+        ],
+    );
+    assert!(success, "{output}");
+    assert!(
+        output.contains("Z ddd-fixture 1.0.0 1.1.0      345 B -> 398 B (+15.4%), 3 -> 3 files\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Z ddd-fixture 1.1.0 2.0.0-rc.1 398 B -> 350 B (-12.1%), 3 -> 3 files\n"),
+        "{output}"
+    );
+    assert!(
+        output.contains("Z total: 743 B -> 748 B (+0.7%), 6 -> 6 files\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn test_local_registry_size_threshold() {
+    let work_dir = source_work_dir("local-registry", "local-registry");
+    let args = ["--size", "ddd-fixture@1.0.0-1.1.0"];
+    let over_threshold = "(+15.4%), 3 -> 3 files [over threshold]";
+
+    std::fs::write(
+        work_dir.path().join(".cargo-ddd.toml"),
+        "size-threshold = 10.0\n",
+    )
+    .unwrap();
+    let (success, output) = run_ddd_in(work_dir.path(), &args);
+    assert!(success, "{output}");
+    assert!(output.contains(over_threshold), "{output}");
+
+    // command line threshold takes precedence over the configured one
+    let (success, output) = run_ddd_in(
+        work_dir.path(),
+        &[
+            "--size-threshold",
+            "20",
+            "--size",
+            "ddd-fixture@1.0.0-1.1.0",
+        ],
+    );
+    assert!(success, "{output}");
+    assert!(!output.contains("[over threshold]"), "{output}");
+
+    std::fs::write(
+        work_dir.path().join(".cargo-ddd.toml"),
+        "size-threshold = 20.0\n",
+    )
+    .unwrap();
+    let (success, output) = run_ddd_in(work_dir.path(), &args);
+    assert!(success, "{output}");
+    assert!(!output.contains("[over threshold]"), "{output}");
+
+    let (success, output) = run_ddd_in(
+        work_dir.path(),
+        &["--size-threshold", "10", "ddd-fixture@1.0.0-1.1.0"],
+    );
+    assert!(success, "{output}");
+    assert!(output.contains(over_threshold), "{output}");
+}